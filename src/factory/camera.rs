@@ -1,11 +1,26 @@
-use bevy::{ecs::system::SystemParam, prelude::*, window::PrimaryWindow};
+use bevy::{
+    ecs::system::SystemParam,
+    picking::{
+        input::PointerInputPlugin,
+        pointer::{PointerId, PointerLocation},
+        PickSet,
+    },
+    prelude::*,
+    window::CursorLeft,
+};
 
 use crate::{layers::FactoryLayer, scheduling::Sets, SCREEN_SIZE};
 
 use super::grid::{GRID_WIDTH, TILE_SIZE};
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(Startup, camera_setup.in_set(Sets::Init));
+    app.add_systems(Startup, camera_setup.in_set(Sets::Init))
+        .add_systems(
+            PreUpdate,
+            cursor_leave
+                .after(PickSet::ProcessInput)
+                .before(PickSet::Backend),
+        );
 }
 
 #[derive(Component, Clone)]
@@ -26,25 +41,38 @@ fn camera_setup(mut commands: Commands) {
     ));
 }
 
+/// Picking doesn't forget where the mouse was when it leaves the window, but nothing should
+/// be hovered then.
+fn cursor_leave(
+    mut cursor_left: EventReader<CursorLeft>,
+    pointer_input: Res<PointerInputPlugin>,
+    mut pointers: Query<(&PointerId, &mut PointerLocation)>,
+) {
+    // replays clear it themselves
+    if cursor_left.read().count() == 0 || !pointer_input.is_mouse_enabled {
+        return;
+    }
+    for (_, mut pointer) in pointers.iter_mut().filter(|(id, _)| id.is_mouse()) {
+        pointer.location = None;
+    }
+}
+
+/// Where the mouse pointer is, as picking sees it. Replays drive the pointer instead of
+/// the real cursor.
 #[derive(SystemParam)]
 pub struct CursorPosition<'w, 's> {
-    window: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    pointers: Query<'w, 's, (&'static PointerId, &'static PointerLocation)>,
     camera: Query<'w, 's, (&'static Camera, &'static GlobalTransform), With<FactoryCamera>>,
 }
 
 impl<'w, 's> CursorPosition<'w, 's> {
     pub fn world(&self) -> Option<Vec2> {
-        if let (Ok(window), Ok((camera, camera_transform))) =
-            (self.window.single(), self.camera.single())
-        {
-            window.cursor_position().and_then(|screen_pos| {
-                camera
-                    .viewport_to_world_2d(camera_transform, screen_pos)
-                    .ok()
-            })
-        } else {
-            None
-        }
+        let (camera, camera_transform) = self.camera.single().ok()?;
+        let (_, pointer) = self.pointers.iter().find(|(id, _)| id.is_mouse())?;
+        let screen_pos = pointer.location()?.position;
+        camera
+            .viewport_to_world_2d(camera_transform, screen_pos)
+            .ok()
     }
     pub fn tile(&self) -> Option<IVec2> {
         self.world().map(|pos| {
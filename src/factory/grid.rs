@@ -2,6 +2,7 @@ use std::f32::consts::PI;
 
 use bevy::prelude::*;
 use lyon_tessellation::{geom::Box2D, path::Winding, StrokeOptions};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     layers::FactoryLayer,
@@ -13,7 +14,7 @@ use crate::{
 };
 
 use super::{
-    machines::{inlet, outlet},
+    machines::{inlet, inlet_logic, outlet, outlet_logic},
     pipe::PipeFlowMaterial,
     tooltip::Tooltip,
 };
@@ -23,6 +24,11 @@ pub(super) fn plugin(app: &mut App) {
         .add_systems(Update, apply_coords.in_set(Sets::PostUpdate));
 }
 
+pub(super) fn simulation_plugin(app: &mut App) {
+    app.add_systems(Startup, grid_spawn_logic.in_set(Sets::Spawn))
+        .add_systems(Update, apply_coords.in_set(Sets::PostUpdate));
+}
+
 pub const GRID_SIZE: usize = 10;
 pub const TILE_SIZE: f32 = 50.0;
 pub const GRID_WIDTH: f32 = GRID_SIZE as f32 * TILE_SIZE;
@@ -59,6 +65,18 @@ impl Grid {
         })
     }
 
+    pub fn buildings(&self) -> impl Iterator<Item = (IVec2, Entity)> + '_ {
+        self.buildings
+            .iter()
+            .enumerate()
+            .filter_map(|(index, building)| {
+                building.map(|entity| {
+                    let pos = ivec2((index % GRID_SIZE) as i32, (index / GRID_SIZE) as i32);
+                    (pos, entity)
+                })
+            })
+    }
+
     fn try_index(&self, pos: IVec2) -> Option<usize> {
         if pos.x >= 0 && pos.y >= 0 && pos.x < GRID_SIZE as i32 && pos.y < GRID_SIZE as i32 {
             Some((pos.y * GRID_SIZE as i32 + pos.x) as usize)
@@ -74,8 +92,6 @@ pub fn grid_spawn(
     mut materials: ResMut<Assets<DitherMaterial>>,
     flow_material: Res<PipeFlowMaterial>,
 ) {
-    let mut tiles = [None; GRID_SIZE * GRID_SIZE];
-    let mut buildings = [None; GRID_SIZE * GRID_SIZE];
     let mesh = meshes.add(Mesh::stroke_with(
         |builder| {
             builder.add_rectangle(
@@ -85,83 +101,147 @@ pub fn grid_spawn(
         },
         &StrokeOptions::default().with_line_width(1.0),
     ));
-    let grid = commands
-        .spawn((
-            Name::new("Grid"),
-            FactoryLayer,
-            Transform::from_xyz(GRID_WIDTH * -0.5, GRID_WIDTH * -0.5, 0.0),
-            Visibility::default(),
-        ))
-        .with_children(|parent| {
-            for y in 1..GRID_SIZE - 1 {
-                for x in 1..GRID_SIZE - 1 {
-                    let tile = parent.spawn((
-                        Name::new("Tile"),
-                        Tile,
-                        FactoryLayer,
-                        TileCoords(ivec2(x as i32, y as i32)),
-                        ZOrder::TILE,
-                        Mesh2d(mesh.clone()),
-                        MeshMaterial2d(SOLID_WHITE),
-                    ));
-                    tiles[y * GRID_SIZE + x] = Some(tile.id());
-                }
-            }
-            for y in [3, 6] {
-                let inlet = parent.spawn((
+    grid_spawn_layout(
+        &mut commands,
+        (FactoryLayer, Visibility::default()),
+        |parent, pos| {
+            parent
+                .spawn((
+                    Name::new("Tile"),
+                    Tile,
+                    FactoryLayer,
+                    TileCoords(pos),
+                    ZOrder::TILE,
+                    Mesh2d(mesh.clone()),
+                    MeshMaterial2d(SOLID_WHITE),
+                ))
+                .id()
+        },
+        |parent, edge| match edge {
+            GridEdge::Inlet(ResourceType::Mineral, pos, dir) => parent
+                .spawn((
                     Tooltip(
                         "Mineral Inlet".to_string(),
                         Some("Provides minerals to connected machines".to_string()),
                     ),
                     inlet(
                         ResourceType::Mineral,
-                        ivec2(0, y),
-                        Direction::Right,
+                        pos,
+                        dir,
                         materials.add(RockyDither {
                             fill: 0.6,
                             scale: 40.0,
+                            // taken from the position rather than the rng, the grid is
+                            // respawned on every undo and load
+                            offset: (pos.as_vec2() * vec2(0.618, 0.382)).fract(),
                         }),
                         flow_material.0.clone(),
                     ),
-                ));
-                buildings[y as usize * GRID_SIZE + 0] = Some(inlet.id());
-            }
-            for y in [3, 6] {
-                let inlet = parent.spawn((
+                ))
+                .id(),
+            GridEdge::Inlet(resource, pos, dir) => parent
+                .spawn((
                     Tooltip(
                         "Gas Inlet".to_string(),
                         Some("Provides gas to connected machines".to_string()),
                     ),
                     inlet(
-                        ResourceType::Gas,
-                        ivec2(GRID_SIZE as i32 - 1, y),
-                        Direction::Left,
+                        resource,
+                        pos,
+                        dir,
                         materials.add(GassyDither {
                             fill: 0.8,
                             scale: 40.0,
                         }),
                         flow_material.0.clone(),
                     ),
-                ));
-                buildings[y as usize * GRID_SIZE + GRID_SIZE - 1] = Some(inlet.id());
-            }
-            for x in [3, 6] {
-                let outlet = parent.spawn((
+                ))
+                .id(),
+            GridEdge::Outlet(pos, dir) => parent
+                .spawn((
                     Tooltip(
                         "Ammo Outlet".to_string(),
-                        Some("Accepts ammo for the ship's weapons".to_string()),
+                        Some("Accepts ammo, rockets, energy and fuel for the ship".to_string()),
                     ),
                     outlet(
-                        ivec2(x, GRID_SIZE as i32 - 1),
-                        Direction::Down,
+                        pos,
+                        dir,
                         materials.add(MetalDither {
                             fill: 0.2,
                             scale: 40.0,
                         }),
                         flow_material.0.clone(),
                     ),
-                ));
-                buildings[(GRID_SIZE - 1) * GRID_SIZE + x as usize] = Some(outlet.id());
+                ))
+                .id(),
+        },
+    );
+}
+
+/// Same layout as [`grid_spawn`], but without any meshes or materials.
+pub fn grid_spawn_logic(mut commands: Commands) {
+    grid_spawn_layout(
+        &mut commands,
+        (),
+        |parent, pos| {
+            parent
+                .spawn((Name::new("Tile"), Tile, TileCoords(pos)))
+                .id()
+        },
+        |parent, edge| match edge {
+            GridEdge::Inlet(resource, pos, dir) => {
+                parent.spawn(inlet_logic(resource, pos, dir)).id()
+            }
+            GridEdge::Outlet(pos, dir) => parent.spawn(outlet_logic(pos, dir)).id(),
+        },
+    );
+}
+
+/// A building along the edge of the grid that every factory starts with
+#[derive(Clone, Copy, Debug)]
+enum GridEdge {
+    Inlet(ResourceType, IVec2, Direction),
+    Outlet(IVec2, Direction),
+}
+
+fn grid_edges() -> impl Iterator<Item = GridEdge> {
+    let last = GRID_SIZE as i32 - 1;
+    let minerals =
+        [3, 6].map(|y| GridEdge::Inlet(ResourceType::Mineral, ivec2(0, y), Direction::Right));
+    let gas = [3, 6].map(|y| GridEdge::Inlet(ResourceType::Gas, ivec2(last, y), Direction::Left));
+    let outlets = [3, 6].map(|x| GridEdge::Outlet(ivec2(x, last), Direction::Down));
+    minerals.into_iter().chain(gas).chain(outlets)
+}
+
+/// Spawns the grid with a tile on every buildable square and the buildings along its edge,
+/// then inserts the [`Grid`] resource. `tile` and `edge` spawn the entities themselves.
+fn grid_spawn_layout(
+    commands: &mut Commands,
+    bundle: impl Bundle,
+    mut tile: impl FnMut(&mut ChildSpawnerCommands, IVec2) -> Entity,
+    mut edge: impl FnMut(&mut ChildSpawnerCommands, GridEdge) -> Entity,
+) {
+    let mut tiles = [None; GRID_SIZE * GRID_SIZE];
+    let mut buildings = [None; GRID_SIZE * GRID_SIZE];
+    let index = |pos: IVec2| pos.y as usize * GRID_SIZE + pos.x as usize;
+    let grid = commands
+        .spawn((
+            Name::new("Grid"),
+            Transform::from_xyz(GRID_WIDTH * -0.5, GRID_WIDTH * -0.5, 0.0),
+            bundle,
+        ))
+        .with_children(|parent| {
+            for y in 1..GRID_SIZE as i32 - 1 {
+                for x in 1..GRID_SIZE as i32 - 1 {
+                    let pos = ivec2(x, y);
+                    tiles[index(pos)] = Some(tile(parent, pos));
+                }
+            }
+            for building in grid_edges() {
+                let pos = match building {
+                    GridEdge::Inlet(_, pos, _) | GridEdge::Outlet(pos, _) => pos,
+                };
+                buildings[index(pos)] = Some(edge(parent, building));
             }
         })
         .id();
@@ -203,7 +283,7 @@ fn apply_coords(mut query: Query<(&TileCoords, &mut Transform), Changed<TileCoor
     }
 }
 
-#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum Direction {
     Right,
     Up,
@@ -245,6 +325,18 @@ impl Direction {
             Direction::Down => 3.0 * PI / 2.0,
         }
     }
+    /// Turns the direction counter-clockwise by `turns` quarter turns
+    pub fn rotate(&self, turns: i32) -> Direction {
+        let index = match self {
+            Direction::Right => 0,
+            Direction::Up => 1,
+            Direction::Left => 2,
+            Direction::Down => 3,
+        };
+        Direction::iter()
+            .nth((index + turns).rem_euclid(4) as usize)
+            .unwrap()
+    }
     pub fn flip(&self) -> Direction {
         match self {
             Direction::Right => Direction::Left,
@@ -0,0 +1,90 @@
+//! Runs the factory simulation without a window, GPU or audio, so factory layouts can be
+//! stepped and inspected from tests.
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use crate::{resources, scheduling};
+
+use super::{
+    machines::rotate_machine, pipe::pipe_logic, pipe_network::InvalidateNetworks,
+    shop::spawn_shop_item_logic,
+};
+
+pub use super::{
+    grid::{Direction, Grid, TileCoords},
+    machines::{Buffer, Inlet},
+    pipe::Pipe,
+    pipe_network::{Branch, InNetwork, PipeNetwork},
+    save::{FactoryLayout, LayoutPath, LoadFactoryLayout, SaveFactoryLayout},
+    shop::ShopItem,
+    time::FactoryTick,
+};
+pub use crate::{
+    resources::{RepairShip, ResourceType, Resources},
+    scheduling::TIMESTEP,
+};
+
+/// Sets up an app that only runs the factory logic. The grid is spawned by the first update,
+/// machine recipes finish loading a few updates later.
+pub fn plugin(app: &mut App) {
+    // recipes are loaded from the assets folder, so the simulation needs an asset server
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        // one fixed step per update
+        .insert_resource(TimeUpdateStrategy::ManualDuration(TIMESTEP))
+        .add_plugins((
+            super::simulation_plugin,
+            resources::plugin,
+            scheduling::plugin,
+        ));
+}
+
+/// Places a shop machine at `pos` the same way dropping it from the shop would, paying
+/// for it out of [`Resources`]. Returns `None` if the tile is not buildable or the machine
+/// is too expensive.
+pub fn place_machine(world: &mut World, item: ShopItem, pos: IVec2) -> Option<Entity> {
+    place_machine_rotated(world, item, pos, 0)
+}
+
+/// Same as [`place_machine`], but turned `turns` quarter turns counter-clockwise first.
+pub fn place_machine_rotated(
+    world: &mut World,
+    item: ShopItem,
+    pos: IVec2,
+    turns: i32,
+) -> Option<Entity> {
+    let cost = item.cost();
+    if !can_build(world, pos) || !cost.affordable(world.resource::<Resources>()) {
+        return None;
+    }
+    cost.pay(&mut world.resource_mut::<Resources>());
+    let entity = spawn_shop_item_logic(&mut world.commands(), item);
+    world.flush();
+    rotate_machine(turns).apply(world.entity_mut(entity));
+    Some(place_building(world, entity, pos))
+}
+
+/// Places a pipe at `pos` the same way drawing it with the mouse would.
+/// Returns `None` if the tile is not buildable.
+pub fn place_pipe(world: &mut World, pos: IVec2) -> Option<Entity> {
+    if !can_build(world, pos) {
+        return None;
+    }
+    let entity = world.spawn(pipe_logic(pos)).id();
+    Some(place_building(world, entity, pos))
+}
+
+fn can_build(world: &World, pos: IVec2) -> bool {
+    let grid = world.resource::<Grid>();
+    grid.get_tile(pos).is_some() && grid.get_building(pos).is_none()
+}
+
+fn place_building(world: &mut World, entity: Entity, pos: IVec2) -> Entity {
+    let mut grid = world.resource_mut::<Grid>();
+    grid.insert_building(pos, entity);
+    let grid_entity = grid.entity;
+    world
+        .entity_mut(entity)
+        .insert((TileCoords(pos), ChildOf(grid_entity)));
+    world.send_event(InvalidateNetworks);
+    entity
+}
@@ -0,0 +1,247 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    game_over::RestartGame,
+    resources::{ResourceType, Resources},
+    scheduling::Sets,
+};
+
+use super::{
+    grid::Grid,
+    machines::Buffer,
+    pipe_network::InvalidateNetworks,
+    save::{apply_layout, FactoryLayout, FactoryLayoutLoaded, LayoutReader},
+    shop::{Cost, PickedUpItem},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<History>()
+        .add_systems(
+            Update,
+            (
+                history_input.in_set(Sets::Input),
+                history_record.in_set(Sets::PostUpdate),
+            ),
+        )
+        .add_observer(history_clear::<RestartGame>)
+        // the history can't pay back what was built before loading
+        .add_observer(history_clear::<FactoryLayoutLoaded>);
+}
+
+/// Oldest edits are forgotten past this many undo steps
+const HISTORY_LIMIT: usize = 100;
+
+/// Snapshots of the grid before and after every edit. Items held by machines and pipes are
+/// left out, so undoing can't be used to duplicate them. Whatever is on the grid when
+/// undoing stays where it is, see [`with_contents`].
+#[derive(Resource)]
+pub struct History {
+    undo: VecDeque<FactoryLayout>,
+    redo: Vec<FactoryLayout>,
+    current: Option<FactoryLayout>,
+    /// The grid changed and has to be recorded once it settles
+    dirty: bool,
+    /// The next recorded layout comes from undo or redo and is not a new edit
+    restoring: bool,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: vec![],
+            current: None,
+            // records the starting grid as the first step
+            dirty: true,
+            restoring: false,
+        }
+    }
+}
+
+impl History {
+    fn record(&mut self, layout: FactoryLayout) {
+        if std::mem::take(&mut self.restoring) {
+            self.current = Some(layout);
+            return;
+        }
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|current| same_buildings(current, &layout))
+        {
+            // only the resources in some buffers changed
+            return;
+        }
+        if let Some(previous) = self.current.replace(layout) {
+            self.undo.push_back(previous);
+            if self.undo.len() > HISTORY_LIMIT {
+                self.undo.pop_front();
+            }
+        }
+        self.redo.clear();
+    }
+
+    /// What going from the current layout to `layout` costs, machines that disappear
+    /// are refunded in full
+    fn cost_of(&self, layout: &FactoryLayout) -> Cost {
+        let current = self
+            .current
+            .as_ref()
+            .map_or(Cost::default(), FactoryLayout::cost);
+        layout.cost() - current
+    }
+
+    fn undo(&mut self, resources: &mut Resources) -> Option<FactoryLayout> {
+        let cost = self.cost_of(self.undo.back()?);
+        if !cost.affordable(resources) {
+            info!("Can't afford to undo, {}", cost.description());
+            return None;
+        }
+        cost.pay(resources);
+        let layout = self.undo.pop_back()?;
+        self.redo.extend(self.current.replace(layout.clone()));
+        self.restoring = true;
+        Some(layout)
+    }
+
+    fn redo(&mut self, resources: &mut Resources) -> Option<FactoryLayout> {
+        let cost = self.cost_of(self.redo.last()?);
+        if !cost.affordable(resources) {
+            info!("Can't afford to redo, {}", cost.description());
+            return None;
+        }
+        cost.pay(resources);
+        let layout = self.redo.pop()?;
+        if let Some(current) = self.current.replace(layout.clone()) {
+            self.undo.push_back(current);
+        }
+        self.restoring = true;
+        Some(layout)
+    }
+}
+
+/// Empties every buffer in the layout, keeping only what is built where.
+fn without_contents(mut layout: FactoryLayout) -> FactoryLayout {
+    for pipe in layout.pipes.iter_mut() {
+        pipe.buffer = None;
+    }
+    for machine in layout.machines.iter_mut() {
+        machine.buffer.1 = 0.0;
+        for port in machine.ports.iter_mut() {
+            port.buffer = port.buffer.take().map(|buffer| Buffer(buffer.0, 0.0));
+        }
+    }
+    layout
+}
+
+/// Fills the buffers of every building in `layout` that is also in `current` with what it
+/// holds right now, so restoring a layout only loses the contents of buildings it removes.
+fn with_contents(mut layout: FactoryLayout, current: &FactoryLayout) -> FactoryLayout {
+    for pipe in layout.pipes.iter_mut() {
+        pipe.buffer = current
+            .pipes
+            .iter()
+            .find(|current| current.coords == pipe.coords)
+            .and_then(|current| current.buffer.clone());
+    }
+    for machine in layout.machines.iter_mut() {
+        let Some(current) = current
+            .machines
+            .iter()
+            .find(|current| current.coords == machine.coords && current.item == machine.item)
+        else {
+            continue;
+        };
+        machine.buffer = current.buffer.clone();
+        for port in machine.ports.iter_mut() {
+            let current = current
+                .ports
+                .iter()
+                .find(|current| current.side == port.side);
+            if let Some(buffer) = current.and_then(|current| current.buffer.clone()) {
+                port.buffer = Some(buffer);
+            }
+        }
+    }
+    layout
+}
+
+fn same_buildings(a: &FactoryLayout, b: &FactoryLayout) -> bool {
+    let buildings = |layout: &FactoryLayout| {
+        let mut layout = layout.clone();
+        for machine in layout.machines.iter_mut() {
+            machine.buffer = Buffer(ResourceType::Mineral, 0.0);
+            for port in machine.ports.iter_mut() {
+                port.buffer = None;
+            }
+        }
+        layout
+    };
+    buildings(a) == buildings(b)
+}
+
+fn history_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut history: ResMut<History>,
+    grid: Res<Grid>,
+    picked_up_item: Option<Res<PickedUpItem>>,
+    mut resources: ResMut<Resources>,
+    reader: LayoutReader,
+) {
+    if picked_up_item.is_some() || history.dirty {
+        return;
+    }
+    let ctrl = keyboard_input.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ]);
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if !ctrl {
+        return;
+    }
+    let layout = if keyboard_input.just_pressed(KeyCode::KeyY)
+        || shift && keyboard_input.just_pressed(KeyCode::KeyZ)
+    {
+        history.redo(&mut resources)
+    } else if keyboard_input.just_pressed(KeyCode::KeyZ) {
+        history.undo(&mut resources)
+    } else {
+        None
+    };
+    if let Some(layout) = layout {
+        info!("Restoring factory layout from history");
+        apply_layout(
+            &mut commands,
+            &grid,
+            with_contents(layout, &reader.layout()),
+        );
+    }
+}
+
+/// Records the grid one frame after it was last changed, so new pipes and ports have had
+/// time to connect. Nothing is recorded while a mouse button is held, which groups a whole
+/// pipe drawing stroke into a single step.
+fn history_record(
+    mut history: ResMut<History>,
+    mut invalidations: EventReader<InvalidateNetworks>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    reader: LayoutReader,
+) {
+    let settled = history.dirty && buttons.get_pressed().next().is_none();
+    if settled {
+        history.dirty = false;
+        history.record(without_contents(reader.layout()));
+    }
+    if invalidations.read().count() > 0 {
+        history.dirty = true;
+    }
+}
+
+fn history_clear<E: Event>(_trigger: Trigger<E>, mut commands: Commands) {
+    commands.insert_resource(History::default());
+}
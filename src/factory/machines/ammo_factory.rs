@@ -1,30 +1,20 @@
 use bevy::{ecs::spawn::SpawnIter, prelude::*};
 
 use crate::{
-    factory::{
-        grid::{Direction, TileCoords},
-        pipe_network::{InNetwork, PipeNetwork},
-        shop::ShopItem,
-        time::FactoryTick,
-        tooltip::Tooltip,
-    },
+    factory::{grid::Direction, shop::ShopItem, tooltip::Tooltip},
     layers::FactoryLayer,
     materials::{DitherMaterial, MetalDither, SOLID_WHITE},
     resources::ResourceType,
-    scheduling::Sets,
     z_order::ZOrder,
 };
 
 use super::{
     meshes::{CONSTRUCTOR_MESH, CONSTRUCTOR_MESH_INNER},
-    port::{machine_port, FlowDirection, MachinePort},
+    port::{machine_port, machine_port_logic, FlowDirection, MachinePort},
+    recipe::Crafter,
     Buffer, Machine,
 };
 
-pub(super) fn plugin(app: &mut App) {
-    app.add_systems(Update, ammo_factory_tick.in_set(Sets::Physics));
-}
-
 #[derive(Component, Clone)]
 #[require(Machine)]
 pub struct AmmoFactory;
@@ -33,16 +23,8 @@ pub fn ammo_factory(
     materials: &mut ResMut<Assets<DitherMaterial>>,
     flow_material: Handle<DitherMaterial>,
 ) -> impl Bundle {
-    let ports = vec![
-        MachinePort::new(Direction::Left, FlowDirection::Inlet),
-        MachinePort::new(Direction::Right, FlowDirection::Outlet),
-    ];
     (
-        Name::new("Ammo Factory"),
-        Machine,
-        AmmoFactory,
-        ShopItem::AmmoFactory,
-        Buffer(ResourceType::Ammo, 0.0),
+        ammo_factory_core(),
         FactoryLayer,
         Mesh2d(CONSTRUCTOR_MESH),
         MeshMaterial2d(SOLID_WHITE),
@@ -63,7 +45,7 @@ pub fn ammo_factory(
                 Transform::from_xyz(0.0, 0.0, 0.2),
             )),
             SpawnIter(
-                ports
+                ammo_factory_ports()
                     .into_iter()
                     .map(move |port| machine_port(port, flow_material.clone())),
             ),
@@ -71,44 +53,30 @@ pub fn ammo_factory(
     )
 }
 
-fn ammo_factory_tick(
-    mut commands: Commands,
-    mut ticks: EventReader<FactoryTick>,
-    machines: Query<(Entity, &Buffer, &Children), (With<AmmoFactory>, With<TileCoords>)>,
-    buffers: Query<&Buffer>,
-    parents: Query<&ChildOf>,
-    networks: Query<&PipeNetwork>,
-    ports: Query<(&MachinePort, &InNetwork)>,
-) -> Result {
-    for _ in ticks.read() {
-        for (entity, buffer, children) in machines.iter() {
-            for child in children.iter() {
-                if let Ok((port, in_network)) = ports.get(child) {
-                    let network = networks.get(in_network.0)?;
-                    if port.flow == FlowDirection::Inlet
-                        && network.resource == ResourceType::Mineral
-                    {
-                        let parent = parents.get(network.source)?;
-                        let source = buffers.get(parent.0)?;
-                        if source.1 < 1.0 || buffer.1 >= 10.0 {
-                            continue;
-                        }
-                        commands
-                            .entity(parent.0)
-                            .insert(Buffer(source.0, source.1 - 1.0));
-                        commands
-                            .entity(entity)
-                            .insert(Buffer(ResourceType::Ammo, buffer.1 + 3.0));
-                        info!(
-                            "Filling ammo factory: {:?} to {}",
-                            ResourceType::Ammo,
-                            buffer.1 + 1.0
-                        );
-                    }
-                }
-            }
-        }
-    }
+/// Ammo factory without meshes or materials, for headless simulations.
+pub fn ammo_factory_logic() -> impl Bundle {
+    (
+        ammo_factory_core(),
+        Children::spawn(SpawnIter(
+            ammo_factory_ports().into_iter().map(machine_port_logic),
+        )),
+    )
+}
+
+fn ammo_factory_core() -> impl Bundle {
+    (
+        Name::new("Ammo Factory"),
+        Machine,
+        AmmoFactory,
+        Crafter::new("recipes/ammo_factory.recipe.ron"),
+        ShopItem::AmmoFactory,
+        Buffer(ResourceType::Ammo, 0.0),
+    )
+}
 
-    Ok(())
+fn ammo_factory_ports() -> Vec<MachinePort> {
+    vec![
+        MachinePort::new(Direction::Left, FlowDirection::Inlet),
+        MachinePort::new(Direction::Right, FlowDirection::Outlet),
+    ]
 }
@@ -0,0 +1,98 @@
+use bevy::{ecs::spawn::SpawnIter, prelude::*};
+
+use crate::{
+    factory::{
+        grid::{Direction, TILE_SIZE},
+        shop::ShopItem,
+        tooltip::Tooltip,
+    },
+    layers::FactoryLayer,
+    materials::{DitherMaterial, GassyDither, SOLID_BLACK, SOLID_WHITE},
+    resources::ResourceType,
+    z_order::ZOrder,
+};
+
+use super::{
+    meshes::{CONSTRUCTOR_MESH, CONSTRUCTOR_MESH_INNER},
+    port::{machine_port, machine_port_logic, FlowDirection, MachinePort},
+    recipe::Crafter,
+    Buffer, Machine,
+};
+
+#[derive(Component, Clone)]
+#[require(Machine)]
+pub struct Generator;
+
+pub fn generator(
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<DitherMaterial>>,
+    flow_material: Handle<DitherMaterial>,
+) -> impl Bundle {
+    let mesh = meshes.add(Annulus::new(TILE_SIZE * 0.15, TILE_SIZE * 0.25));
+    (
+        generator_core(),
+        FactoryLayer,
+        Mesh2d(CONSTRUCTOR_MESH),
+        MeshMaterial2d(SOLID_WHITE),
+        ZOrder::MACHINE,
+        Tooltip(
+            "Generator".to_string(),
+            Some(
+                "Burns gas from the left\nand produces energy to the right\nfor the ship's shield"
+                    .to_string(),
+            ),
+        ),
+        Children::spawn((
+            Spawn((
+                Name::new("Generator Inner"),
+                FactoryLayer,
+                Mesh2d(CONSTRUCTOR_MESH_INNER),
+                MeshMaterial2d(materials.add(GassyDither {
+                    fill: 0.5,
+                    scale: 40.0,
+                })),
+                Transform::from_xyz(0.0, 0.0, 0.3),
+            )),
+            Spawn((
+                Name::new("Generator Inner 2"),
+                FactoryLayer,
+                Mesh2d(mesh),
+                MeshMaterial2d(SOLID_BLACK),
+                Transform::from_xyz(0.0, 0.0, 0.4),
+            )),
+            SpawnIter(
+                generator_ports()
+                    .into_iter()
+                    .map(move |port| machine_port(port, flow_material.clone())),
+            ),
+        )),
+    )
+}
+
+/// Generator without meshes or materials, for headless simulations.
+pub fn generator_logic() -> impl Bundle {
+    (
+        generator_core(),
+        Children::spawn(SpawnIter(
+            generator_ports().into_iter().map(machine_port_logic),
+        )),
+    )
+}
+
+fn generator_core() -> impl Bundle {
+    (
+        Name::new("Generator"),
+        Machine,
+        Generator,
+        Crafter::new("recipes/generator.recipe.ron"),
+        ShopItem::Generator,
+        Buffer(ResourceType::Energy, 0.0),
+    )
+}
+
+fn generator_ports() -> Vec<MachinePort> {
+    vec![
+        MachinePort::new(Direction::Left, FlowDirection::Inlet),
+        MachinePort::new(Direction::Right, FlowDirection::Outlet),
+    ]
+}
@@ -2,30 +2,24 @@ use bevy::{ecs::spawn::SpawnIter, prelude::*};
 
 use crate::{
     factory::{
-        grid::{Direction, TileCoords, TILE_SIZE},
-        pipe_network::{InNetwork, PipeNetwork},
+        grid::{Direction, TILE_SIZE},
         shop::ShopItem,
-        time::FactoryTick,
         tooltip::Tooltip,
     },
     layers::FactoryLayer,
     materials::{DitherMaterial, MetalDither, SOLID_WHITE},
     mesh::MeshLyonExtensions,
-    resources::{ResourceType, Resources},
-    scheduling::Sets,
+    resources::ResourceType,
     z_order::ZOrder,
 };
 
 use super::{
     meshes::CONSTRUCTOR_MESH,
-    port::{machine_port, FlowDirection, MachinePort},
+    port::{machine_port, machine_port_logic, FlowDirection, MachinePort},
+    recipe::Crafter,
     Buffer, Machine,
 };
 
-pub(super) fn plugin(app: &mut App) {
-    app.add_systems(Update, hull_fixer_tick.in_set(Sets::Physics));
-}
-
 #[derive(Component, Clone)]
 #[require(Machine)]
 pub struct HullFixer;
@@ -35,7 +29,6 @@ pub fn hull_fixer(
     materials: &mut ResMut<Assets<DitherMaterial>>,
     flow_material: Handle<DitherMaterial>,
 ) -> impl Bundle {
-    let ports = vec![MachinePort::new(Direction::Up, FlowDirection::Inlet)];
     const BIG: f32 = TILE_SIZE * 0.4;
     const SMALL: f32 = TILE_SIZE * 0.1;
     let vertices = vec![
@@ -50,11 +43,7 @@ pub fn hull_fixer(
     ];
     let mesh = meshes.add(Mesh::fill_polygon(&vertices));
     (
-        Name::new("Hull Fixer"),
-        Machine,
-        HullFixer,
-        ShopItem::HullFixer,
-        Buffer(ResourceType::Mineral, 0.0),
+        hull_fixer_core(),
         FactoryLayer,
         Mesh2d(CONSTRUCTOR_MESH),
         MeshMaterial2d(SOLID_WHITE),
@@ -75,7 +64,7 @@ pub fn hull_fixer(
                 Transform::from_xyz(0.0, 0.0, 0.3),
             )),
             SpawnIter(
-                ports
+                hull_fixer_ports()
                     .into_iter()
                     .map(move |port| machine_port(port, flow_material.clone())),
             ),
@@ -83,42 +72,27 @@ pub fn hull_fixer(
     )
 }
 
-fn hull_fixer_tick(
-    mut commands: Commands,
-    mut ticks: EventReader<FactoryTick>,
-    machines: Query<&Children, (With<HullFixer>, With<TileCoords>)>,
-    buffers: Query<&Buffer>,
-    parents: Query<&ChildOf>,
-    networks: Query<&PipeNetwork>,
-    ports: Query<(&MachinePort, &InNetwork)>,
-    mut resources: ResMut<Resources>,
-) -> Result {
-    for _ in ticks.read() {
-        for children in machines.iter() {
-            for child in children.iter() {
-                if let Ok((port, in_network)) = ports.get(child) {
-                    let network = networks.get(in_network.0)?;
-                    if port.flow == FlowDirection::Inlet
-                        && network.resource == ResourceType::Mineral
-                    {
-                        let parent = parents.get(network.source)?;
-                        let source = buffers.get(parent.0)?;
-                        if source.1 < 1.0 {
-                            continue;
-                        }
-                        if resources.health < 100.0 {
-                            let new_health = (resources.health + 20.0).min(100.0);
-                            resources.health = new_health;
-                            commands
-                                .entity(parent.0)
-                                .insert(Buffer(source.0, source.1 - 1.0));
-                            info!("Repairing hull: {} to {}", resources.health, new_health);
-                        }
-                    }
-                }
-            }
-        }
-    }
+/// Hull fixer without meshes or materials, for headless simulations.
+pub fn hull_fixer_logic() -> impl Bundle {
+    (
+        hull_fixer_core(),
+        Children::spawn(SpawnIter(
+            hull_fixer_ports().into_iter().map(machine_port_logic),
+        )),
+    )
+}
+
+fn hull_fixer_core() -> impl Bundle {
+    (
+        Name::new("Hull Fixer"),
+        Machine,
+        HullFixer,
+        Crafter::new("recipes/hull_fixer.recipe.ron"),
+        ShopItem::HullFixer,
+        Buffer(ResourceType::Mineral, 0.0),
+    )
+}
 
-    Ok(())
+fn hull_fixer_ports() -> Vec<MachinePort> {
+    vec![MachinePort::new(Direction::Up, FlowDirection::Inlet)]
 }
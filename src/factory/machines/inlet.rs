@@ -11,18 +11,17 @@ use crate::{
 
 use super::{
     meshes::{INLET_MESH, INLET_MESH_INNER},
-    port::{machine_port, FlowDirection, MachinePort},
+    port::{machine_port, machine_port_logic, FlowDirection, MachinePort},
     Buffer, Machine,
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(
-        Update,
-        (
-            inlet_fill.in_set(Sets::Physics),
-            inlet_update_material.in_set(Sets::PostUpdate),
-        ),
-    );
+    simulation_plugin(app);
+    app.add_systems(Update, inlet_update_material.in_set(Sets::PostUpdate));
+}
+
+pub(super) fn simulation_plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, inlet_fill.in_set(Sets::Physics));
 }
 
 #[derive(Component, Clone)]
@@ -37,13 +36,10 @@ pub fn inlet(
     flow_material: Handle<DitherMaterial>,
 ) -> impl Bundle {
     (
-        Name::new(format!("{} Inlet", resource.to_string())),
-        Inlet(resource),
-        Buffer(resource, 0.0),
+        inlet_core(resource, coords),
         FactoryLayer,
         Mesh2d(INLET_MESH),
         MeshMaterial2d(SOLID_WHITE),
-        TileCoords(coords),
         ZOrder::MACHINE,
         children![
             (
@@ -61,6 +57,26 @@ pub fn inlet(
     )
 }
 
+/// Inlet without meshes or materials, for headless simulations.
+pub fn inlet_logic(resource: ResourceType, coords: IVec2, dir: Direction) -> impl Bundle {
+    (
+        inlet_core(resource, coords),
+        children![machine_port_logic(MachinePort::new(
+            dir,
+            FlowDirection::Outlet
+        ))],
+    )
+}
+
+fn inlet_core(resource: ResourceType, coords: IVec2) -> impl Bundle {
+    (
+        Name::new(format!("{} Inlet", resource.to_string())),
+        Inlet(resource),
+        Buffer(resource, 0.0),
+        TileCoords(coords),
+    )
+}
+
 fn inlet_fill(mut inlets: Query<(&mut Buffer, &Inlet)>, mut resources: ResMut<Resources>) {
     for (mut buffer, inlet) in inlets.iter_mut() {
         if resources.get(inlet.0) >= 1.0 && buffer.1 < 10.0 {
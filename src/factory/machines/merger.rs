@@ -0,0 +1,151 @@
+use bevy::{ecs::spawn::SpawnIter, prelude::*};
+
+use crate::{
+    factory::{
+        grid::{Direction, TileCoords, TILE_SIZE},
+        pipe_network::{InNetwork, InvalidateNetworks, PipeNetwork},
+        shop::ShopItem,
+        time::FactoryTick,
+        tooltip::Tooltip,
+    },
+    layers::FactoryLayer,
+    materials::{DitherMaterial, SOLID_BLACK, SOLID_WHITE},
+    mesh::MeshLyonExtensions,
+    resources::ResourceType,
+    scheduling::Sets,
+    z_order::ZOrder,
+};
+
+use super::{
+    meshes::{CONSTRUCTOR_MESH, CONSTRUCTOR_MESH_INNER},
+    port::{machine_port, machine_port_logic, FlowDirection, MachinePort},
+    Buffer, Machine,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, merger_tick.in_set(Sets::Physics));
+}
+
+/// Items the merger can hold before it stops pulling from its inlets
+const MERGER_CAPACITY: f32 = 5.0;
+
+#[derive(Component, Clone)]
+#[require(Machine)]
+pub struct Merger;
+
+pub fn merger(
+    meshes: &mut ResMut<Assets<Mesh>>,
+    flow_material: Handle<DitherMaterial>,
+) -> impl Bundle {
+    const SIZE: f32 = TILE_SIZE * 0.25;
+    let vertices = vec![vec2(-SIZE, SIZE), vec2(SIZE, 0.0), vec2(-SIZE, -SIZE)];
+    (
+        merger_core(),
+        FactoryLayer,
+        Mesh2d(CONSTRUCTOR_MESH),
+        MeshMaterial2d(SOLID_WHITE),
+        ZOrder::MACHINE,
+        Tooltip(
+            "Merger".to_string(),
+            Some(
+                "Combines input from the left, top\nand bottom into one output to the right"
+                    .to_string(),
+            ),
+        ),
+        Children::spawn((
+            Spawn((
+                Name::new("Merger Inner"),
+                FactoryLayer,
+                Mesh2d(CONSTRUCTOR_MESH_INNER),
+                MeshMaterial2d(SOLID_BLACK),
+                Transform::from_xyz(0.0, 0.0, 0.2),
+            )),
+            Spawn((
+                Name::new("Merger Arrow"),
+                FactoryLayer,
+                Mesh2d(meshes.add(Mesh::fill_polygon(&vertices))),
+                MeshMaterial2d(SOLID_WHITE),
+                Transform::from_xyz(0.0, 0.0, 0.3),
+            )),
+            SpawnIter(
+                merger_ports()
+                    .into_iter()
+                    .map(move |port| machine_port(port, flow_material.clone())),
+            ),
+        )),
+    )
+}
+
+/// Merger without meshes or materials, for headless simulations.
+pub fn merger_logic() -> impl Bundle {
+    (
+        merger_core(),
+        Children::spawn(SpawnIter(
+            merger_ports().into_iter().map(machine_port_logic),
+        )),
+    )
+}
+
+fn merger_core() -> impl Bundle {
+    (
+        Name::new("Merger"),
+        Machine,
+        Merger,
+        ShopItem::Merger,
+        Buffer(ResourceType::Mineral, 0.0),
+    )
+}
+
+fn merger_ports() -> Vec<MachinePort> {
+    vec![
+        MachinePort::new(Direction::Left, FlowDirection::Inlet),
+        MachinePort::new(Direction::Up, FlowDirection::Inlet),
+        MachinePort::new(Direction::Down, FlowDirection::Inlet),
+        MachinePort::new(Direction::Right, FlowDirection::Outlet),
+    ]
+}
+
+fn merger_tick(
+    mut ticks: EventReader<FactoryTick>,
+    machines: Query<(Entity, &Children), (With<Merger>, With<TileCoords>)>,
+    mut buffers: Query<&mut Buffer>,
+    networks: Query<&PipeNetwork>,
+    ports: Query<(Entity, &MachinePort, &InNetwork)>,
+    mut invalidate: EventWriter<InvalidateNetworks>,
+) -> Result {
+    for _ in ticks.read() {
+        for (entity, children) in machines.iter() {
+            for (port_entity, port, in_network) in
+                children.iter().filter_map(|child| ports.get(child).ok())
+            {
+                if port.flow != FlowDirection::Inlet {
+                    continue;
+                }
+                let network = networks.get(in_network.0)?;
+                let Some(sink_buffer) = network.sink_buffer(port_entity) else {
+                    continue;
+                };
+                if sink_buffer == entity {
+                    // piped straight back into itself
+                    continue;
+                }
+                let [mut source, mut buffer] = buffers.get_many_mut([sink_buffer, entity])?;
+                if source.1 < 1.0 || buffer.1 >= MERGER_CAPACITY {
+                    continue;
+                }
+                if buffer.0 != source.0 {
+                    // only one resource fits through the outlet at a time
+                    if buffer.1 > 0.0 {
+                        continue;
+                    }
+                    buffer.0 = source.0;
+                    invalidate.write(InvalidateNetworks);
+                }
+                source.1 -= 1.0;
+                buffer.1 += 1.0;
+            }
+        }
+    }
+
+    Ok(())
+}
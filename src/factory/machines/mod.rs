@@ -1,39 +1,114 @@
+use std::f32::consts::FRAC_PI_2;
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::resources::ResourceType;
 
+use super::grid::Direction;
+
 mod ammo_factory;
+mod generator;
 mod hull_fixer;
 mod inlet;
+mod merger;
 mod meshes;
 mod outlet;
 mod pipe_switch;
 mod port;
+mod recipe;
+mod refinery;
 mod rocket_factory;
+mod splitter;
+mod weapon_workshop;
 
-pub use ammo_factory::ammo_factory;
-pub use hull_fixer::hull_fixer;
-pub use inlet::{inlet, Inlet};
-pub use outlet::outlet;
-pub use pipe_switch::pipe_switch;
-pub use port::{FlowDirection, MachinePort};
-pub use rocket_factory::rocket_factory;
+pub use ammo_factory::{ammo_factory, ammo_factory_logic};
+pub use generator::{generator, generator_logic};
+pub use hull_fixer::{hull_fixer, hull_fixer_logic};
+pub use inlet::{inlet, inlet_logic, Inlet};
+pub use merger::{merger, merger_logic};
+pub use outlet::{outlet, outlet_logic, Outlet, TANK_SIZE};
+pub use pipe_switch::{pipe_switch, pipe_switch_logic};
+pub use port::{machine_port_logic, rotated_machine_port, FlowDirection, MachinePort};
+pub use refinery::{refinery, refinery_logic};
+pub use rocket_factory::{rocket_factory, rocket_factory_logic};
+pub use splitter::{splitter, splitter_logic, SplitMode, Splitter};
+pub use weapon_workshop::{weapon_workshop, weapon_workshop_logic};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
-        ammo_factory::plugin,
-        hull_fixer::plugin,
         inlet::plugin,
+        merger::plugin,
         meshes::plugin,
         outlet::plugin,
         pipe_switch::plugin,
         port::plugin,
-        rocket_factory::plugin,
+        recipe::plugin,
+        splitter::plugin,
+    ));
+}
+
+/// Registers only the machine logic, without any rendering or pointer interaction.
+pub(super) fn simulation_plugin(app: &mut App) {
+    app.add_plugins((
+        inlet::simulation_plugin,
+        merger::plugin,
+        outlet::plugin,
+        pipe_switch::simulation_plugin,
+        port::plugin,
+        recipe::plugin,
+        splitter::simulation_plugin,
     ));
 }
 
 #[derive(Component, Clone, Default)]
+#[require(MachineRotation)]
 pub struct Machine;
 
-#[derive(Component, Clone, Debug)]
+/// Quarter turns counter-clockwise from the machine's default orientation. Port sides
+/// are always stored as they face on the grid, this is only needed to map them back.
+#[derive(Component, Clone, Copy, Default, Debug)]
+pub struct MachineRotation(pub u8);
+
+impl MachineRotation {
+    pub fn to_local(self, side: Direction) -> Direction {
+        side.rotate(-(self.0 as i32))
+    }
+
+    pub fn to_world(self, side: Direction) -> Direction {
+        side.rotate(self.0 as i32)
+    }
+
+    pub fn quat(self) -> Quat {
+        Quat::from_rotation_z(self.0 as f32 * FRAC_PI_2)
+    }
+}
+
+/// Turns a machine, its meshes and all of its ports by `turns` quarter turns
+/// counter-clockwise.
+pub fn rotate_machine(turns: i32) -> impl EntityCommand {
+    move |mut entity: EntityWorldMut| {
+        let Some(mut rotation) = entity.get_mut::<MachineRotation>() else {
+            return;
+        };
+        rotation.0 = (rotation.0 as i32 + turns).rem_euclid(4) as u8;
+        let quat = rotation.quat();
+        if let Some(mut transform) = entity.get_mut::<Transform>() {
+            transform.rotation = quat;
+        }
+        let children = entity
+            .get::<Children>()
+            .map(|children| children.to_vec())
+            .unwrap_or_default();
+        entity.world_scope(|world| {
+            for child in children {
+                if let Some(mut port) = world.get_mut::<MachinePort>(child) {
+                    port.side = port.side.rotate(turns);
+                }
+            }
+        });
+    }
+}
+
+#[derive(Component, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Buffer(pub ResourceType, pub f32);
@@ -14,14 +14,17 @@ use crate::{
 
 use super::{
     meshes::{INLET_MESH, INLET_MESH_INNER},
-    port::{machine_port, FlowDirection, MachinePort},
+    port::{machine_port, machine_port_logic, FlowDirection, MachinePort},
     Buffer, Machine,
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(Update, outlet_fill.in_set(Sets::Physics));
+    app.add_systems(FixedUpdate, outlet_fill.in_set(Sets::Physics));
 }
 
+/// Outlets stop topping up a ship's resource once it holds this much
+pub const TANK_SIZE: f32 = 10.0;
+
 #[derive(Component, Clone)]
 #[require(Machine)]
 pub struct Outlet;
@@ -33,12 +36,10 @@ pub fn outlet(
     flow_material: Handle<DitherMaterial>,
 ) -> impl Bundle {
     (
-        Name::new("Outlet"),
-        Outlet,
+        outlet_core(coords),
         FactoryLayer,
         Mesh2d(INLET_MESH),
         MeshMaterial2d(SOLID_WHITE),
-        TileCoords(coords),
         ZOrder::MACHINE,
         children![
             (
@@ -56,30 +57,53 @@ pub fn outlet(
     )
 }
 
+/// Outlet without meshes or materials, for headless simulations.
+pub fn outlet_logic(coords: IVec2, dir: Direction) -> impl Bundle {
+    (
+        outlet_core(coords),
+        children![machine_port_logic(MachinePort::new(
+            dir,
+            FlowDirection::Inlet
+        ))],
+    )
+}
+
+fn outlet_core(coords: IVec2) -> impl Bundle {
+    (Name::new("Outlet"), Outlet, TileCoords(coords))
+}
+
 fn outlet_fill(
     outlets: Query<&Children, With<Outlet>>,
     ports: Query<&InNetwork>,
     mut resources: ResMut<Resources>,
     networks: Query<&PipeNetwork>,
     mut buffers: Query<&mut Buffer>,
-    parents: Query<&ChildOf>,
 ) -> Result {
     for children in outlets.iter() {
-        let in_network = children.iter().find_map(|child| ports.get(child).ok());
-        if let Some(in_network) = in_network {
+        let in_network = children
+            .iter()
+            .find_map(|child| ports.get(child).ok().map(|in_network| (child, in_network)));
+        if let Some((port, in_network)) = in_network {
             let network = networks.get(in_network.0)?;
-            if network.resource == ResourceType::Rockets || network.resource == ResourceType::Ammo {
-                if let Ok(parent) = parents.get(network.source).map(|p| p.parent()) {
-                    if let Ok(mut buffer) = buffers.get_mut(parent) {
-                        if buffer.1 > 0.0 && resources.get(network.resource) < 10.0 {
-                            info!(
-                                "Filling outlet: {:?} to {}",
-                                network.resource,
-                                resources.get(network.resource) + 1.0
-                            );
-                            resources.add(network.resource, 1.0);
-                            buffer.1 -= 1.0;
-                        }
+            let Some(sink_buffer) = network.sink_buffer(port) else {
+                continue;
+            };
+            if matches!(
+                network.resource,
+                ResourceType::Rockets
+                    | ResourceType::Ammo
+                    | ResourceType::Energy
+                    | ResourceType::Fuel
+            ) {
+                if let Ok(mut buffer) = buffers.get_mut(sink_buffer) {
+                    if buffer.1 > 0.0 && resources.get(network.resource) < TANK_SIZE {
+                        info!(
+                            "Filling outlet: {:?} to {}",
+                            network.resource,
+                            resources.get(network.resource) + 1.0
+                        );
+                        resources.add(network.resource, 1.0);
+                        buffer.1 -= 1.0;
                     }
                 }
             }
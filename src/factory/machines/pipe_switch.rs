@@ -5,8 +5,8 @@ use crate::{
         grid::{Direction, Grid, TileCoords, TILE_SIZE},
         machines::{
             meshes::{CONSTRUCTOR_MESH, CONSTRUCTOR_MESH_INNER},
-            port::machine_port,
-            Buffer, FlowDirection, MachinePort,
+            port::{machine_port, machine_port_logic, rotated_machine_port},
+            Buffer, FlowDirection, MachinePort, MachineRotation,
         },
         pipe::{pipe_bundle, Pipe, PipeFlowMaterial},
         pipe_network::{InNetwork, InvalidateNetworks, PipeNetwork},
@@ -26,15 +26,17 @@ use crate::{
 use super::Machine;
 
 pub(super) fn plugin(app: &mut App) {
+    simulation_plugin(app);
     app.add_systems(
         Update,
-        (
-            pipe_switch_tick.in_set(Sets::Physics),
-            pipe_switch_observers.in_set(Sets::PostUpdate),
-        ),
+        (pipe_switch_observers, pipe_switch_update_handle).in_set(Sets::PostUpdate),
     );
 }
 
+pub(super) fn simulation_plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, pipe_switch_tick.in_set(Sets::Physics));
+}
+
 #[derive(Component, Clone)]
 #[require(Machine)]
 pub struct PipeSwitch;
@@ -46,10 +48,6 @@ pub fn pipe_switch(
     meshes: &mut ResMut<Assets<Mesh>>,
     flow_material: Handle<DitherMaterial>,
 ) -> impl Bundle {
-    let ports = vec![
-        MachinePort::new(Direction::Left, FlowDirection::Inlet),
-        MachinePort::new(Direction::Right, FlowDirection::Outlet),
-    ];
     const SMALL: f32 = TILE_SIZE * 0.1;
     const BIG: f32 = TILE_SIZE * 0.2;
     let vertices = vec![
@@ -62,11 +60,7 @@ pub fn pipe_switch(
         vec2(-BIG, SMALL),
     ];
     (
-        Name::new("Pipe Switch"),
-        Machine,
-        PipeSwitch,
-        ShopItem::PipeSwitch,
-        Buffer(ResourceType::Mineral, 0.0),
+        pipe_switch_core(),
         FactoryLayer,
         Mesh2d(CONSTRUCTOR_MESH),
         MeshMaterial2d(SOLID_WHITE),
@@ -92,7 +86,7 @@ pub fn pipe_switch(
                 Transform::from_xyz(0.0, 0.0, 0.3),
             )),
             SpawnIter(
-                ports
+                pipe_switch_ports()
                     .into_iter()
                     .map(move |port| machine_port(port, flow_material.clone())),
             ),
@@ -100,27 +94,57 @@ pub fn pipe_switch(
     )
 }
 
+/// Pipe switch without meshes or materials, for headless simulations.
+pub fn pipe_switch_logic() -> impl Bundle {
+    (
+        pipe_switch_core(),
+        Children::spawn(SpawnIter(
+            pipe_switch_ports().into_iter().map(machine_port_logic),
+        )),
+    )
+}
+
+fn pipe_switch_core() -> impl Bundle {
+    (
+        Name::new("Pipe Switch"),
+        Machine,
+        PipeSwitch,
+        ShopItem::PipeSwitch,
+        Buffer(ResourceType::Mineral, 0.0),
+    )
+}
+
+fn pipe_switch_ports() -> Vec<MachinePort> {
+    vec![
+        MachinePort::new(Direction::Left, FlowDirection::Inlet),
+        MachinePort::new(Direction::Right, FlowDirection::Outlet),
+    ]
+}
+
 fn pipe_switch_tick(
     mut commands: Commands,
     mut ticks: EventReader<FactoryTick>,
     machines: Query<(Entity, &Buffer, &Children), (With<PipeSwitch>, With<TileCoords>)>,
     buffers: Query<&Buffer>,
-    parents: Query<&ChildOf>,
     networks: Query<&PipeNetwork>,
-    ports: Query<(&MachinePort, &InNetwork)>,
+    ports: Query<(Entity, &MachinePort, &InNetwork)>,
 ) -> Result {
     for _ in ticks.read() {
         for (entity, buffer, children) in machines.iter() {
-            for (port, in_network) in children.iter().filter_map(|child| ports.get(child).ok()) {
+            for (port_entity, port, in_network) in
+                children.iter().filter_map(|child| ports.get(child).ok())
+            {
                 let network = networks.get(in_network.0)?;
                 if port.flow == FlowDirection::Inlet && network.resource == ResourceType::Mineral {
-                    let parent = parents.get(network.source)?;
-                    let source = buffers.get(parent.0)?;
+                    let Some(sink_buffer) = network.sink_buffer(port_entity) else {
+                        continue;
+                    };
+                    let source = buffers.get(sink_buffer)?;
                     if source.1 < 1.0 || buffer.1 >= 5.0 {
                         continue;
                     }
                     commands
-                        .entity(parent.0)
+                        .entity(sink_buffer)
                         .insert(Buffer(source.0, source.1 - 1.0));
                     commands
                         .entity(entity)
@@ -140,36 +164,52 @@ fn pipe_switch_observers(mut commands: Commands, switches: Query<Entity, Added<P
     }
 }
 
+fn pipe_switch_update_handle(
+    mut commands: Commands,
+    switches: Query<(&Children, &MachineRotation), (With<PipeSwitch>, Changed<Children>)>,
+    ports: Query<&MachinePort>,
+    handles: Query<Entity, With<PipeSwitchHandle>>,
+) {
+    for (children, rotation) in switches.iter() {
+        let outlet = children
+            .iter()
+            .filter_map(|child| ports.get(child).ok())
+            .find(|port| port.flow == FlowDirection::Outlet);
+        let handle = children.iter().find(|child| handles.contains(*child));
+        if let (Some(outlet), Some(handle)) = (outlet, handle) {
+            commands
+                .entity(handle)
+                .insert(
+                    Transform::from_xyz(0.0, 0.0, 0.3).with_rotation(Quat::from_rotation_z(
+                        rotation.to_local(outlet.side).angle(),
+                    )),
+                );
+        }
+    }
+}
+
 fn pipe_switch_click(
     trigger: Trigger<Pointer<Click>>,
     mut commands: Commands,
     flow_material: Res<PipeFlowMaterial>,
-    pipe_switches: Query<(Entity, &Children, &TileCoords), With<PipeSwitch>>,
+    pipe_switches: Query<(Entity, &Children, &TileCoords, &MachineRotation), With<PipeSwitch>>,
     ports: Query<&MachinePort>,
     mut grid: ResMut<Grid>,
     pipes: Query<&Pipe>,
     mut invalidate_networks: EventWriter<InvalidateNetworks>,
     sounds: Res<Sounds>,
-    handles: Query<&PipeSwitchHandle>,
 ) {
     let target = trigger.target();
-    if let Ok((switch, children, coords)) = pipe_switches.get(target) {
+    if let Ok((switch, children, coords, rotation)) = pipe_switches.get(target) {
         for child in children.iter() {
             if let Ok(port) = ports.get(child) {
                 if port.flow == FlowDirection::Outlet {
-                    let new_dir = match port.side {
+                    let new_dir = rotation.to_world(match rotation.to_local(port.side) {
                         Direction::Right => Direction::Down,
                         Direction::Down => Direction::Up,
                         Direction::Up => Direction::Right,
                         _ => unreachable!(),
-                    };
-                    let handle = children.iter().find(|child| handles.contains(*child));
-                    if let Some(handle) = handle {
-                        commands.entity(handle).insert(
-                            Transform::from_xyz(0.0, 0.0, 0.3)
-                                .with_rotation(Quat::from_rotation_z(new_dir.angle())),
-                        );
-                    }
+                    });
                     let old_coords = coords.0 + port.side.as_ivec2();
                     let new_coords = coords.0 + new_dir.as_ivec2();
                     for coords in [old_coords, new_coords] {
@@ -183,8 +223,9 @@ fn pipe_switch_click(
                         }
                     }
                     commands.entity(child).despawn();
-                    commands.entity(switch).with_child(machine_port(
+                    commands.entity(switch).with_child(rotated_machine_port(
                         MachinePort::new(new_dir, FlowDirection::Outlet),
+                        *rotation,
                         flow_material.0.clone(),
                     ));
                     invalidate_networks.write(InvalidateNetworks);
@@ -1,19 +1,21 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     factory::{
         grid::{Direction, Grid, TileCoords},
         pipe::{pipe_bridge, Pipe},
+        pipe_network::InvalidateNetworks,
     },
     materials::DitherMaterial,
     scheduling::Sets,
 };
 
-use super::Machine;
+use super::{Machine, MachineRotation};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<MachinePort>()
-        .add_systems(Update, machine_port_connect.in_set(Sets::Update))
+        .add_systems(FixedUpdate, machine_port_connect.in_set(Sets::Update))
         .add_observer(machine_port_cleanup);
 }
 
@@ -34,31 +36,45 @@ impl MachinePort {
     }
 }
 
-#[derive(Reflect, Clone, PartialEq, Eq, Debug)]
+#[derive(Reflect, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum FlowDirection {
     Inlet,
     Outlet,
 }
 
 pub fn machine_port(port: MachinePort, flow_material: Handle<DitherMaterial>) -> impl Bundle {
+    rotated_machine_port(port, MachineRotation::default(), flow_material)
+}
+
+/// Port for a machine that has already been rotated, `port.side` is the side it faces on
+/// the grid.
+pub fn rotated_machine_port(
+    port: MachinePort,
+    rotation: MachineRotation,
+    flow_material: Handle<DitherMaterial>,
+) -> impl Bundle {
+    let bridge = pipe_bridge(
+        port.flow == FlowDirection::Inlet,
+        rotation.to_local(port.side),
+        flow_material,
+    );
     (
-        Name::new("Machine Port"),
-        Transform::IDENTITY,
         Visibility::Inherited,
-        children![pipe_bridge(
-            port.flow == FlowDirection::Inlet,
-            port.side,
-            flow_material
-        )],
-        port,
+        children![bridge],
+        machine_port_logic(port),
     )
 }
 
+pub fn machine_port_logic(port: MachinePort) -> impl Bundle {
+    (Name::new("Machine Port"), Transform::IDENTITY, port)
+}
+
 fn machine_port_connect(
     mut ports: Query<(Entity, &ChildOf, Mut<MachinePort>)>,
     machines: Query<(&TileCoords, &Children), With<Machine>>,
     mut pipes: Query<&mut Pipe>,
     grid: Res<Grid>,
+    mut invalidate: EventWriter<InvalidateNetworks>,
 ) {
     let added_ports = ports
         .iter()
@@ -84,6 +100,7 @@ fn machine_port_connect(
                             ports.get_many_mut([port_entity, nbr_port_entity]).unwrap();
                         port.connected = Some(nbr_port_entity);
                         nbr_port.connected = Some(port_entity);
+                        invalidate.write(InvalidateNetworks);
                     }
                 } else if pipes.contains(neighbor) {
                     // neighbor is a pipe
@@ -92,9 +109,11 @@ fn machine_port_connect(
                     if port.flow == FlowDirection::Inlet && nbr_pipe.to.is_none() {
                         port.connected = Some(neighbor);
                         nbr_pipe.to = Some(port_entity);
+                        invalidate.write(InvalidateNetworks);
                     } else if port.flow == FlowDirection::Outlet && nbr_pipe.from.is_none() {
                         port.connected = Some(neighbor);
                         nbr_pipe.from = Some(port_entity);
+                        invalidate.write(InvalidateNetworks);
                     }
                 }
             }
@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    factory::{
+        grid::{Direction, TileCoords},
+        pipe::PIPE_CAPACITY,
+        pipe_network::{InNetwork, PipeNetwork},
+        time::FactoryTick,
+    },
+    resources::{RepairShip, ResourceType, Resources, UpgradeWeapon, MAX_HEALTH},
+    ron_asset::{RonAsset, RonLoader},
+    scheduling::Sets,
+};
+
+use super::{Buffer, FlowDirection, Machine, MachinePort, MachineRotation};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Recipe>()
+        .init_asset_loader::<RonLoader<Recipe>>()
+        .add_systems(
+            FixedUpdate,
+            (
+                crafter_load_recipe.in_set(Sets::PreUpdate),
+                crafter_tick.in_set(Sets::Physics),
+            ),
+        );
+}
+
+/// What a machine consumes and produces, loaded from `assets/recipes/*.recipe.ron`.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct Recipe {
+    /// Each input is taken from the inlet port on its side
+    pub inputs: Vec<RecipeInput>,
+    pub outputs: Vec<RecipeOutput>,
+    /// Factory ticks the inputs have to be available before the recipe completes
+    pub ticks: u32,
+    /// The machine stops crafting once its buffer holds this much
+    pub buffer_cap: f32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RecipeInput {
+    /// Side of the unrotated machine the input comes in through
+    pub side: Direction,
+    pub resource: ResourceType,
+    pub amount: f32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum RecipeOutput {
+    /// Added to the machine's buffer for its outlet
+    Item { resource: ResourceType, amount: f32 },
+    /// Repairs the ship's hull, only crafted while it is damaged
+    Health(f32),
+    /// Upgrades one of the ship's weapons
+    WeaponUpgrade,
+}
+
+impl RonAsset for Recipe {
+    const EXTENSIONS: &'static [&'static str] = &["recipe.ron"];
+
+    /// Inputs are taken from a single inlet port, which never holds more than a pipe
+    fn validate(&self) -> Result<(), String> {
+        match self
+            .inputs
+            .iter()
+            .find(|input| input.amount > PIPE_CAPACITY)
+        {
+            Some(input) => Err(format!(
+                "needs {} {:?} at once, but a port only holds {PIPE_CAPACITY}",
+                input.amount, input.resource
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Machine that runs the recipe at `path`, relative to the assets folder.
+#[derive(Component, Clone, Debug)]
+#[require(Machine)]
+pub struct Crafter {
+    pub path: &'static str,
+    progress: u32,
+}
+
+impl Crafter {
+    pub fn new(path: &'static str) -> Self {
+        Self { path, progress: 0 }
+    }
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct CrafterRecipe(pub Handle<Recipe>);
+
+fn crafter_load_recipe(
+    mut commands: Commands,
+    crafters: Query<(Entity, &Crafter), Without<CrafterRecipe>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, crafter) in crafters.iter() {
+        commands
+            .entity(entity)
+            .insert(CrafterRecipe(asset_server.load(crafter.path)));
+    }
+}
+
+fn crafter_tick(
+    mut commands: Commands,
+    mut ticks: EventReader<FactoryTick>,
+    mut machines: Query<
+        (
+            Entity,
+            &mut Crafter,
+            &CrafterRecipe,
+            &MachineRotation,
+            &Children,
+        ),
+        With<TileCoords>,
+    >,
+    recipes: Res<Assets<Recipe>>,
+    mut buffers: Query<&mut Buffer>,
+    networks: Query<&PipeNetwork>,
+    ports: Query<(Entity, &MachinePort, &InNetwork)>,
+    resources: Res<Resources>,
+) -> Result {
+    for _ in ticks.read() {
+        for (entity, mut crafter, recipe, rotation, children) in machines.iter_mut() {
+            let Some(recipe) = recipes.get(&recipe.0) else {
+                continue;
+            };
+            let buffer = buffers.get(entity)?;
+            let has_room = recipe.outputs.iter().all(|output| match output {
+                RecipeOutput::Item { .. } => buffer.1 < recipe.buffer_cap,
+                RecipeOutput::Health(_) => resources.health < MAX_HEALTH,
+                RecipeOutput::WeaponUpgrade => true,
+            });
+            if !has_room {
+                continue;
+            }
+
+            let mut sources = vec![];
+            for input in recipe.inputs.iter() {
+                let side = rotation.to_world(input.side);
+                let source = children
+                    .iter()
+                    .filter_map(|child| ports.get(child).ok())
+                    .find(|(_, port, _)| port.flow == FlowDirection::Inlet && port.side == side)
+                    .and_then(|(port, _, in_network)| {
+                        let network = networks.get(in_network.0).ok()?;
+                        (network.resource == input.resource)
+                            .then(|| network.sink_buffer(port))
+                            .flatten()
+                    })
+                    .filter(|source| {
+                        buffers
+                            .get(*source)
+                            .is_ok_and(|source| source.1 >= input.amount)
+                    });
+                if let Some(source) = source {
+                    sources.push((source, input.amount));
+                }
+            }
+            if sources.len() < recipe.inputs.len() {
+                continue;
+            }
+
+            crafter.progress += 1;
+            if crafter.progress < recipe.ticks {
+                continue;
+            }
+            crafter.progress = 0;
+            for (source, amount) in sources {
+                buffers.get_mut(source)?.1 -= amount;
+            }
+            for output in recipe.outputs.iter() {
+                match output {
+                    RecipeOutput::Item { resource, amount } => {
+                        let mut buffer = buffers.get_mut(entity)?;
+                        buffer.0 = *resource;
+                        buffer.1 += amount;
+                        info!("Crafted {amount} {:?}, buffer at {}", resource, buffer.1);
+                    }
+                    RecipeOutput::Health(amount) => {
+                        info!("Repairing hull by {amount} from {}", resources.health);
+                        commands.trigger(RepairShip(*amount));
+                    }
+                    RecipeOutput::WeaponUpgrade => {
+                        info!("Crafted a weapon upgrade");
+                        commands.trigger(UpgradeWeapon);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_recipes_fit_in_a_port() {
+        for entry in std::fs::read_dir("assets/recipes").unwrap() {
+            let path = entry.unwrap().path();
+            let recipe: Recipe = ron::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            assert_eq!(recipe.validate(), Ok(()), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn recipes_needing_more_than_a_port_holds_are_refused() {
+        let recipe: Recipe = ron::from_str(
+            "(
+                inputs: [(side: Left, resource: Mineral, amount: 4.0)],
+                outputs: [Item(resource: Ammo, amount: 1.0)],
+                ticks: 1,
+                buffer_cap: 10.0,
+            )",
+        )
+        .unwrap();
+        assert!(recipe.validate().is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use bevy::{ecs::spawn::SpawnIter, prelude::*};
+
+use crate::{
+    factory::{
+        grid::{Direction, TILE_SIZE},
+        shop::ShopItem,
+        tooltip::Tooltip,
+    },
+    layers::FactoryLayer,
+    materials::{DitherMaterial, GassyDither, SOLID_BLACK, SOLID_WHITE},
+    resources::ResourceType,
+    z_order::ZOrder,
+};
+
+use super::{
+    meshes::{CONSTRUCTOR_MESH, CONSTRUCTOR_MESH_INNER},
+    port::{machine_port, machine_port_logic, FlowDirection, MachinePort},
+    recipe::Crafter,
+    Buffer, Machine,
+};
+
+#[derive(Component, Clone)]
+#[require(Machine)]
+pub struct Refinery;
+
+pub fn refinery(
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<DitherMaterial>>,
+    flow_material: Handle<DitherMaterial>,
+) -> impl Bundle {
+    let mesh = meshes.add(Rectangle::new(TILE_SIZE * 0.2, TILE_SIZE * 0.5));
+    (
+        refinery_core(),
+        FactoryLayer,
+        Mesh2d(CONSTRUCTOR_MESH),
+        MeshMaterial2d(SOLID_WHITE),
+        ZOrder::MACHINE,
+        Tooltip(
+            "Refinery".to_string(),
+            Some(
+                "Refines gas from the left\ninto fuel to the right\nfor the ship's thrusters"
+                    .to_string(),
+            ),
+        ),
+        Children::spawn((
+            Spawn((
+                Name::new("Refinery Inner"),
+                FactoryLayer,
+                Mesh2d(CONSTRUCTOR_MESH_INNER),
+                MeshMaterial2d(materials.add(GassyDither {
+                    fill: 0.7,
+                    scale: 40.0,
+                })),
+                Transform::from_xyz(0.0, 0.0, 0.3),
+            )),
+            Spawn((
+                Name::new("Refinery Inner 2"),
+                FactoryLayer,
+                Mesh2d(mesh),
+                MeshMaterial2d(SOLID_BLACK),
+                Transform::from_xyz(0.0, 0.0, 0.4),
+            )),
+            SpawnIter(
+                refinery_ports()
+                    .into_iter()
+                    .map(move |port| machine_port(port, flow_material.clone())),
+            ),
+        )),
+    )
+}
+
+/// Refinery without meshes or materials, for headless simulations.
+pub fn refinery_logic() -> impl Bundle {
+    (
+        refinery_core(),
+        Children::spawn(SpawnIter(
+            refinery_ports().into_iter().map(machine_port_logic),
+        )),
+    )
+}
+
+fn refinery_core() -> impl Bundle {
+    (
+        Name::new("Refinery"),
+        Machine,
+        Refinery,
+        Crafter::new("recipes/refinery.recipe.ron"),
+        ShopItem::Refinery,
+        Buffer(ResourceType::Fuel, 0.0),
+    )
+}
+
+fn refinery_ports() -> Vec<MachinePort> {
+    vec![
+        MachinePort::new(Direction::Left, FlowDirection::Inlet),
+        MachinePort::new(Direction::Right, FlowDirection::Outlet),
+    ]
+}
@@ -0,0 +1,382 @@
+use bevy::{ecs::spawn::SpawnIter, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    factory::{
+        grid::{Direction, TileCoords, TILE_SIZE},
+        pipe_network::{InNetwork, PipeNetwork},
+        shop::ShopItem,
+        time::FactoryTick,
+        tooltip::Tooltip,
+    },
+    layers::FactoryLayer,
+    materials::{DitherMaterial, SOLID_BLACK, SOLID_WHITE},
+    mesh::MeshLyonExtensions,
+    resources::ResourceType,
+    scheduling::Sets,
+    sounds::Sounds,
+    z_order::ZOrder,
+};
+
+use super::{
+    meshes::{CONSTRUCTOR_MESH, CONSTRUCTOR_MESH_INNER},
+    port::{machine_port, machine_port_logic, FlowDirection, MachinePort},
+    Buffer, Machine, MachineRotation,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    simulation_plugin(app);
+    app.add_systems(
+        Update,
+        (splitter_observers, splitter_update_tooltip).in_set(Sets::PostUpdate),
+    );
+}
+
+pub(super) fn simulation_plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, splitter_tick.in_set(Sets::Physics));
+}
+
+/// Outlet sides, in priority order
+pub const SPLITTER_OUTLETS: [Direction; 3] = [Direction::Right, Direction::Up, Direction::Down];
+/// Share of the items each outlet gets in [`SplitMode::Proportional`]
+const PROPORTIONAL_WEIGHTS: [f32; 3] = [2.0, 1.0, 1.0];
+/// Items each outlet can hold before the splitter skips it
+const OUTLET_CAPACITY: f32 = 3.0;
+/// Items moved from the inlet per factory tick
+const THROUGHPUT: usize = 3;
+
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub enum SplitMode {
+    /// Takes turns between the outlets
+    #[default]
+    RoundRobin,
+    /// Fills the straight outlet first, then up, then down
+    Priority,
+    /// Sends half the items straight through and a quarter up and down each
+    Proportional,
+}
+
+impl SplitMode {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::RoundRobin => "Round robin: takes turns between outputs",
+            Self::Priority => "Priority: fills right, then up, then down",
+            Self::Proportional => "Proportional: half right, a quarter up and down",
+        }
+    }
+
+    fn next(&self) -> Self {
+        match self {
+            Self::RoundRobin => Self::Priority,
+            Self::Priority => Self::Proportional,
+            Self::Proportional => Self::RoundRobin,
+        }
+    }
+}
+
+#[derive(Component, Clone, Default, Debug)]
+#[require(Machine)]
+pub struct Splitter {
+    pub mode: SplitMode,
+    cursor: usize,
+    delivered: [f32; 3],
+}
+
+impl Splitter {
+    pub fn new(mode: SplitMode) -> Self {
+        Self { mode, ..default() }
+    }
+
+    /// Picks the outlet the next item goes to, out of the ones that can currently take it.
+    pub fn next_outlet(&mut self, available: [bool; 3]) -> Option<usize> {
+        let index = match self.mode {
+            SplitMode::RoundRobin => (0..3)
+                .map(|offset| (self.cursor + offset) % 3)
+                .find(|&i| available[i]),
+            SplitMode::Priority => (0..3).find(|&i| available[i]),
+            SplitMode::Proportional => (0..3).filter(|&i| available[i]).min_by(|&a, &b| {
+                let a = self.delivered[a] / PROPORTIONAL_WEIGHTS[a];
+                let b = self.delivered[b] / PROPORTIONAL_WEIGHTS[b];
+                a.total_cmp(&b)
+            }),
+        }?;
+        self.cursor = (index + 1) % 3;
+        self.delivered[index] += 1.0;
+        Some(index)
+    }
+
+    pub fn cycle_mode(&mut self) {
+        *self = Self::new(self.mode.next());
+    }
+}
+
+pub fn splitter(
+    meshes: &mut ResMut<Assets<Mesh>>,
+    flow_material: Handle<DitherMaterial>,
+) -> impl Bundle {
+    const SMALL: f32 = TILE_SIZE * 0.08;
+    const BIG: f32 = TILE_SIZE * 0.3;
+    let vertices = vec![
+        vec2(-BIG, -SMALL),
+        vec2(0.0, -SMALL),
+        vec2(0.0, -BIG),
+        vec2(SMALL * 2.0, -BIG),
+        vec2(SMALL * 2.0, -SMALL),
+        vec2(BIG, -SMALL),
+        vec2(BIG, SMALL),
+        vec2(SMALL * 2.0, SMALL),
+        vec2(SMALL * 2.0, BIG),
+        vec2(0.0, BIG),
+        vec2(0.0, SMALL),
+        vec2(-BIG, SMALL),
+    ];
+    let inlet_material = flow_material.clone();
+    (
+        splitter_core(),
+        FactoryLayer,
+        Mesh2d(CONSTRUCTOR_MESH),
+        MeshMaterial2d(SOLID_WHITE),
+        ZOrder::MACHINE,
+        splitter_tooltip(SplitMode::default()),
+        Children::spawn((
+            Spawn((
+                Name::new("Splitter Inner"),
+                FactoryLayer,
+                Mesh2d(CONSTRUCTOR_MESH_INNER),
+                MeshMaterial2d(SOLID_BLACK),
+                Transform::from_xyz(0.0, 0.0, 0.2),
+            )),
+            Spawn((
+                Name::new("Splitter Fork"),
+                FactoryLayer,
+                Mesh2d(meshes.add(Mesh::fill_polygon(&vertices))),
+                MeshMaterial2d(SOLID_WHITE),
+                Transform::from_xyz(0.0, 0.0, 0.3),
+            )),
+            Spawn(machine_port(splitter_inlet(), inlet_material)),
+            SpawnIter(splitter_outlets().map(move |port| {
+                (
+                    machine_port(port, flow_material.clone()),
+                    Buffer(ResourceType::Mineral, 0.0),
+                )
+            })),
+        )),
+    )
+}
+
+/// Splitter without meshes or materials, for headless simulations.
+pub fn splitter_logic() -> impl Bundle {
+    (
+        splitter_core(),
+        Children::spawn((
+            Spawn(machine_port_logic(splitter_inlet())),
+            SpawnIter(
+                splitter_outlets()
+                    .map(|port| (machine_port_logic(port), Buffer(ResourceType::Mineral, 0.0))),
+            ),
+        )),
+    )
+}
+
+fn splitter_core() -> impl Bundle {
+    (
+        Name::new("Splitter"),
+        Machine,
+        Splitter::default(),
+        ShopItem::Splitter,
+        // items are held by the outlet ports, this stays empty
+        Buffer(ResourceType::Mineral, 0.0),
+    )
+}
+
+fn splitter_tooltip(mode: SplitMode) -> Tooltip {
+    Tooltip(
+        "Splitter".to_string(),
+        Some(format!(
+            "Splits input from the left\nbetween the other three sides\n{}\nClick to change mode",
+            mode.description()
+        )),
+    )
+}
+
+fn splitter_inlet() -> MachinePort {
+    MachinePort::new(Direction::Left, FlowDirection::Inlet)
+}
+
+fn splitter_outlets() -> impl Iterator<Item = MachinePort> {
+    SPLITTER_OUTLETS
+        .into_iter()
+        .map(|side| MachinePort::new(side, FlowDirection::Outlet))
+}
+
+fn splitter_tick(
+    mut ticks: EventReader<FactoryTick>,
+    mut splitters: Query<(&mut Splitter, &MachineRotation, &Children), With<TileCoords>>,
+    ports: Query<(&MachinePort, &InNetwork)>,
+    networks: Query<&PipeNetwork>,
+    mut buffers: Query<&mut Buffer>,
+) -> Result {
+    for _ in ticks.read() {
+        for (mut splitter, rotation, children) in splitters.iter_mut() {
+            let port = |side: Direction, flow: FlowDirection| {
+                let side = rotation.to_world(side);
+                children.iter().find(|child| {
+                    ports
+                        .get(*child)
+                        .is_ok_and(|(port, _)| port.side == side && port.flow == flow)
+                })
+            };
+            // the splitter's outlets are part of the network feeding it
+            let Some(inlet) = port(Direction::Left, FlowDirection::Inlet) else {
+                continue;
+            };
+            let Some(network) = ports
+                .get(inlet)
+                .ok()
+                .and_then(|(_, in_network)| networks.get(in_network.0).ok())
+            else {
+                continue;
+            };
+            let Some(source) = network.sink_buffer(inlet) else {
+                continue;
+            };
+            let resource = network.resource;
+            // only outlets that lead somewhere other than back into the splitter get items
+            let outlets = SPLITTER_OUTLETS.map(|side| {
+                port(side, FlowDirection::Outlet).filter(|outlet| {
+                    network
+                        .branch_from(*outlet)
+                        .and_then(|branch| branch.sink)
+                        .is_some_and(|sink| sink != inlet)
+                })
+            });
+
+            for _ in 0..THROUGHPUT {
+                if buffers.get(source).is_ok_and(|source| source.1 < 1.0) {
+                    break;
+                }
+                let available = outlets.map(|outlet| {
+                    outlet
+                        .and_then(|outlet| buffers.get(outlet).ok())
+                        .is_some_and(|buffer| {
+                            buffer.1 <= 0.0 || buffer.0 == resource && buffer.1 < OUTLET_CAPACITY
+                        })
+                });
+                let Some(index) = splitter.next_outlet(available) else {
+                    break;
+                };
+                let [mut source, mut outlet] =
+                    buffers.get_many_mut([source, outlets[index].unwrap()])?;
+                source.1 -= 1.0;
+                outlet.0 = resource;
+                outlet.1 += 1.0;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn splitter_observers(mut commands: Commands, splitters: Query<Entity, Added<Splitter>>) {
+    for entity in splitters.iter() {
+        commands.entity(entity).observe(splitter_click);
+    }
+}
+
+fn splitter_update_tooltip(
+    mut commands: Commands,
+    splitters: Query<(Entity, &Splitter), (Changed<Splitter>, With<Tooltip>)>,
+) {
+    for (entity, splitter) in splitters.iter() {
+        commands
+            .entity(entity)
+            .insert(splitter_tooltip(splitter.mode));
+    }
+}
+
+fn splitter_click(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut splitters: Query<&mut Splitter, With<TileCoords>>,
+    sounds: Res<Sounds>,
+) {
+    let target = trigger.target();
+    if let Ok(mut splitter) = splitters.get_mut(target) {
+        splitter.cycle_mode();
+        commands.spawn((
+            Name::new("Splitter Toggle Sound"),
+            AudioPlayer::new(sounds.switch.clone()),
+            PlaybackSettings::DESPAWN,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(mode: SplitMode, available: [bool; 3], items: usize) -> Vec<usize> {
+        let mut splitter = Splitter::new(mode);
+        (0..items)
+            .filter_map(|_| splitter.next_outlet(available))
+            .collect()
+    }
+
+    #[test]
+    fn round_robin_takes_turns() {
+        assert_eq!(
+            split(SplitMode::RoundRobin, [true; 3], 6),
+            [0, 1, 2, 0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn round_robin_skips_unavailable_outlets() {
+        assert_eq!(
+            split(SplitMode::RoundRobin, [true, false, true], 4),
+            [0, 2, 0, 2]
+        );
+    }
+
+    #[test]
+    fn priority_fills_the_first_available_outlet() {
+        assert_eq!(split(SplitMode::Priority, [true; 3], 3), [0, 0, 0]);
+        assert_eq!(
+            split(SplitMode::Priority, [false, true, true], 3),
+            [1, 1, 1]
+        );
+    }
+
+    #[test]
+    fn proportional_sends_half_straight_through() {
+        let outlets = split(SplitMode::Proportional, [true; 3], 8);
+        let count = |outlet| outlets.iter().filter(|&&i| i == outlet).count();
+        assert_eq!([count(0), count(1), count(2)], [4, 2, 2]);
+    }
+
+    #[test]
+    fn proportional_shares_between_available_outlets() {
+        let outlets = split(SplitMode::Proportional, [false, true, true], 4);
+        assert_eq!(outlets, [1, 2, 1, 2]);
+    }
+
+    #[test]
+    fn nothing_to_split_into() {
+        for mode in [
+            SplitMode::RoundRobin,
+            SplitMode::Priority,
+            SplitMode::Proportional,
+        ] {
+            assert!(split(mode, [false; 3], 1).is_empty());
+        }
+    }
+
+    #[test]
+    fn changing_mode_starts_over() {
+        let mut splitter = Splitter::new(SplitMode::Proportional);
+        splitter.next_outlet([true; 3]);
+        splitter.cycle_mode();
+        assert_eq!(splitter.mode, SplitMode::RoundRobin);
+        assert_eq!(splitter.next_outlet([true; 3]), Some(0));
+    }
+}
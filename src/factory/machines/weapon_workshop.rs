@@ -0,0 +1,98 @@
+use bevy::{ecs::spawn::SpawnIter, prelude::*};
+
+use crate::{
+    factory::{
+        grid::{Direction, TILE_SIZE},
+        shop::ShopItem,
+        tooltip::Tooltip,
+    },
+    layers::FactoryLayer,
+    materials::{DitherMaterial, MetalDither, SOLID_BLACK, SOLID_WHITE},
+    resources::ResourceType,
+    z_order::ZOrder,
+};
+
+use super::{
+    meshes::{CONSTRUCTOR_MESH, CONSTRUCTOR_MESH_INNER},
+    port::{machine_port, machine_port_logic, FlowDirection, MachinePort},
+    recipe::Crafter,
+    Buffer, Machine,
+};
+
+#[derive(Component, Clone)]
+#[require(Machine)]
+pub struct WeaponWorkshop;
+
+pub fn weapon_workshop(
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<DitherMaterial>>,
+    flow_material: Handle<DitherMaterial>,
+) -> impl Bundle {
+    let mesh = meshes.add(Rhombus::new(TILE_SIZE * 0.5, TILE_SIZE * 0.5));
+    (
+        weapon_workshop_core(),
+        FactoryLayer,
+        Mesh2d(CONSTRUCTOR_MESH),
+        MeshMaterial2d(SOLID_WHITE),
+        ZOrder::MACHINE,
+        Tooltip(
+            "Weapon Workshop".to_string(),
+            Some(
+                "Consumes minerals from the left\nand gas from below\nto upgrade the ship's weapons"
+                    .to_string(),
+            ),
+        ),
+        Children::spawn((
+            Spawn((
+                Name::new("Weapon Workshop Inner"),
+                FactoryLayer,
+                Mesh2d(CONSTRUCTOR_MESH_INNER),
+                MeshMaterial2d(materials.add(MetalDither {
+                    fill: 0.6,
+                    scale: 40.0,
+                })),
+                Transform::from_xyz(0.0, 0.0, 0.3),
+            )),
+            Spawn((
+                Name::new("Weapon Workshop Inner 2"),
+                FactoryLayer,
+                Mesh2d(mesh),
+                MeshMaterial2d(SOLID_BLACK),
+                Transform::from_xyz(0.0, 0.0, 0.4),
+            )),
+            SpawnIter(
+                weapon_workshop_ports()
+                    .into_iter()
+                    .map(move |port| machine_port(port, flow_material.clone())),
+            ),
+        )),
+    )
+}
+
+/// Weapon workshop without meshes or materials, for headless simulations.
+pub fn weapon_workshop_logic() -> impl Bundle {
+    (
+        weapon_workshop_core(),
+        Children::spawn(SpawnIter(
+            weapon_workshop_ports().into_iter().map(machine_port_logic),
+        )),
+    )
+}
+
+fn weapon_workshop_core() -> impl Bundle {
+    (
+        Name::new("Weapon Workshop"),
+        Machine,
+        WeaponWorkshop,
+        Crafter::new("recipes/weapon_workshop.recipe.ron"),
+        ShopItem::WeaponWorkshop,
+        Buffer(ResourceType::Mineral, 0.0),
+    )
+}
+
+fn weapon_workshop_ports() -> Vec<MachinePort> {
+    vec![
+        MachinePort::new(Direction::Left, FlowDirection::Inlet),
+        MachinePort::new(Direction::Down, FlowDirection::Inlet),
+    ]
+}
@@ -2,10 +2,13 @@ use bevy::prelude::*;
 
 mod camera;
 mod grid;
+pub mod headless;
+mod history;
 mod machines;
 mod pipe;
 mod pipe_network;
 mod restart;
+mod save;
 mod shop;
 mod time;
 mod tooltip;
@@ -15,13 +18,27 @@ pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         camera::plugin,
         grid::plugin,
+        history::plugin,
         machines::plugin,
         pipe::plugin,
         pipe_network::plugin,
         restart::plugin,
+        save::plugin,
         shop::plugin,
         time::plugin,
         tooltip::plugin,
         ui::plugin,
     ));
 }
+
+/// Registers only the factory simulation: grid, pipes, networks, machines and the factory clock.
+pub(super) fn simulation_plugin(app: &mut App) {
+    app.add_plugins((
+        grid::simulation_plugin,
+        machines::simulation_plugin,
+        pipe::simulation_plugin,
+        pipe_network::plugin,
+        save::simulation_plugin,
+        time::plugin,
+    ));
+}
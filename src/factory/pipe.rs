@@ -3,6 +3,7 @@ use bevy::{asset::weak_handle, prelude::*};
 use crate::{
     layers::FactoryLayer,
     materials::{Dither, DitherMaterial, SOLID_BLACK, SOLID_WHITE},
+    resources::ResourceType,
     scheduling::Sets,
     z_order::ZOrder,
 };
@@ -10,7 +11,7 @@ use crate::{
 use super::{
     camera::CursorPosition,
     grid::{Direction, Grid, TileCoords, TILE_SIZE},
-    machines::{FlowDirection, Machine, MachinePort},
+    machines::{Buffer, FlowDirection, Machine, MachinePort},
     pipe_network::InvalidateNetworks,
     shop::PickedUpItem,
     time::TimeScale,
@@ -40,19 +41,36 @@ pub(super) fn plugin(app: &mut App) {
         Rectangle::new(TILE_SIZE * 0.8 + 4.0, PIPE_SIZE).into(),
     );
 
+    simulation_plugin(app);
     app.add_systems(Startup, pipe_setup_materials.in_set(Sets::Init))
         .add_systems(
             Update,
             (
                 pipe_draw.in_set(Sets::Input),
-                pipe_connect.in_set(Sets::Physics),
-                (pipe_update_material, pipe_make_bridges).in_set(Sets::PostUpdate),
+                (
+                    pipe_add_contents,
+                    pipe_update_contents,
+                    pipe_update_material,
+                    pipe_make_bridges,
+                )
+                    .in_set(Sets::PostUpdate),
             ),
-        )
+        );
+}
+
+pub(super) fn simulation_plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, pipe_connect.in_set(Sets::Physics))
         .add_observer(pipe_cleanup_connections);
 }
 
 const PIPE_SIZE: f32 = TILE_SIZE * 0.6;
+/// Items a single pipe can hold in transit
+pub const PIPE_CAPACITY: f32 = 3.0;
+/// Items a pipe can pass on to the next one per factory tick
+pub const PIPE_THROUGHPUT: f32 = 2.0;
+/// Dither fill of an empty pipe, so the flow stays visible
+const PIPE_EMPTY_FILL: f32 = 0.01;
+const PIPE_FULL_FILL: f32 = 0.4;
 
 #[derive(Component, Clone, Default, Debug)]
 pub struct Pipe {
@@ -62,10 +80,8 @@ pub struct Pipe {
 
 pub fn pipe_bundle(pos: IVec2) -> impl Bundle {
     (
-        Name::new("Pipe"),
-        Pipe::default(),
+        pipe_logic(pos),
         FactoryLayer,
-        TileCoords(pos),
         ZOrder::PIPE,
         Mesh2d(PIPE_MESH),
         MeshMaterial2d(SOLID_WHITE),
@@ -79,6 +95,17 @@ pub fn pipe_bundle(pos: IVec2) -> impl Bundle {
     )
 }
 
+/// Pipe without meshes or materials, for headless simulations.
+pub fn pipe_logic(pos: IVec2) -> impl Bundle {
+    (
+        Name::new("Pipe"),
+        Pipe::default(),
+        TileCoords(pos),
+        // items in transit
+        Buffer(ResourceType::Mineral, 0.0),
+    )
+}
+
 fn pipe_draw(
     mut commands: Commands,
     pipes: Query<&Pipe>,
@@ -267,6 +294,47 @@ pub fn pipe_bridge(
 #[derive(Resource)]
 pub struct PipeFlowMaterial(pub Handle<DitherMaterial>);
 
+/// Material showing how full a single pipe is
+#[derive(Component)]
+pub struct PipeContents(pub Handle<DitherMaterial>);
+
+fn pipe_add_contents(
+    mut commands: Commands,
+    pipes: Query<Entity, (Added<Pipe>, With<Mesh2d>)>,
+    mut materials: ResMut<Assets<DitherMaterial>>,
+) {
+    for pipe in pipes.iter() {
+        let material = materials.add(Dither {
+            fill: PIPE_EMPTY_FILL,
+            scale: 44.0,
+            ..default()
+        });
+        commands
+            .entity(pipe)
+            .insert(PipeContents(material.clone()))
+            .with_child((
+                Name::new("Pipe Contents"),
+                FactoryLayer,
+                Pickable::IGNORE, // https://github.com/bevyengine/bevy/issues/19181
+                Mesh2d(PIPE_MESH_INNER),
+                MeshMaterial2d(material),
+                Transform::from_xyz(0.0, 0.0, 0.02),
+            ));
+    }
+}
+
+fn pipe_update_contents(
+    pipes: Query<(&Buffer, &PipeContents), Or<(Changed<Buffer>, Added<PipeContents>)>>,
+    mut materials: ResMut<Assets<DitherMaterial>>,
+) {
+    for (buffer, contents) in pipes.iter() {
+        if let Some(material) = materials.get_mut(&contents.0) {
+            let fullness = (buffer.1 / PIPE_CAPACITY).clamp(0.0, 1.0);
+            material.settings.fill = PIPE_EMPTY_FILL.lerp(PIPE_FULL_FILL, fullness);
+        }
+    }
+}
+
 fn pipe_setup_materials(mut commands: Commands, mut materials: ResMut<Assets<DitherMaterial>>) {
     let material = materials.add(Dither {
         fill: 0.01,
@@ -276,13 +344,33 @@ fn pipe_setup_materials(mut commands: Commands, mut materials: ResMut<Assets<Dit
     commands.insert_resource(PipeFlowMaterial(material));
 }
 
+/// Scrolls the flow while the factory runs. Pipe contents follow along, but only materials
+/// that are out of step get touched, every write re-uploads the material.
 fn pipe_update_material(
     material: Res<PipeFlowMaterial>,
     time: Res<Time>,
     timescale: Res<TimeScale>,
+    pipes: Query<&PipeContents>,
+    added: Query<(), Added<PipeContents>>,
     mut materials: ResMut<Assets<DitherMaterial>>,
 ) {
-    if let Some(material) = materials.get_mut(&material.0) {
-        material.settings.offset.x -= time.delta_secs() * timescale.0 * 10.0;
+    let scroll = time.delta_secs() * timescale.0 * 10.0;
+    if scroll == 0.0 && added.is_empty() {
+        return;
+    }
+    let Some(flow) = materials.get(&material.0) else {
+        return;
+    };
+    let offset = flow.settings.offset - vec2(scroll, 0.0);
+    if scroll != 0.0 {
+        materials.get_mut(&material.0).unwrap().settings.offset = offset;
+    }
+    for contents in pipes.iter() {
+        let out_of_step = materials
+            .get(&contents.0)
+            .is_some_and(|material| material.settings.offset != offset);
+        if out_of_step {
+            materials.get_mut(&contents.0).unwrap().settings.offset = offset;
+        }
     }
 }
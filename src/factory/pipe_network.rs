@@ -7,8 +7,9 @@ use crate::{
 
 use super::{
     grid::TileCoords,
-    machines::{Buffer, Machine, MachinePort},
-    pipe::Pipe,
+    machines::{Buffer, Machine, MachinePort, Splitter},
+    pipe::{Pipe, PIPE_CAPACITY, PIPE_THROUGHPUT},
+    time::FactoryTick,
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -16,23 +17,57 @@ pub(super) fn plugin(app: &mut App) {
         .register_type::<InNetwork>()
         .register_type::<PipeNetwork>()
         .add_systems(
-            Update,
-            (
-                network_rebuild.in_set(Sets::Update),
-                network_debug.in_set(Sets::PostUpdate),
-            ),
+            FixedUpdate,
+            (network_rebuild, network_flow).chain().in_set(Sets::Update),
         )
+        .add_systems(Update, network_debug.in_set(Sets::PostUpdate))
         .add_event::<InvalidateNetworks>();
 }
 
 #[derive(Reflect, Component, Debug)]
 pub struct PipeNetwork {
     pub source: Entity,
+    /// Runs of pipe the items travel along. The first one leaves the source, splitters on
+    /// the way start a new branch at each of their outlets.
+    pub branches: Vec<Branch>,
     pub resource: ResourceType,
-    pub sink: Option<Entity>,
     pub material: Handle<DitherMaterial>,
 }
 
+#[derive(Reflect, Clone, Debug)]
+pub struct Branch {
+    /// Entity holding the [`Buffer`] items enter the branch from: the port starting it if
+    /// it has its own buffer, otherwise the machine it belongs to
+    pub start: Entity,
+    /// Pipes of the branch, in order from start to sink
+    pub pipes: Vec<Entity>,
+    /// Machine port the branch ends at
+    pub sink: Option<Entity>,
+    /// Entity holding the [`Buffer`] the sink draws from: the last pipe of the branch,
+    /// or `start` when the machines are right next to each other
+    pub sink_buffer: Entity,
+}
+
+impl PipeNetwork {
+    /// Every machine port that draws items out of the network
+    pub fn sinks(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.branches.iter().filter_map(|branch| branch.sink)
+    }
+
+    /// Buffer the machine port `sink` draws items from
+    pub fn sink_buffer(&self, sink: Entity) -> Option<Entity> {
+        self.branches
+            .iter()
+            .find(|branch| branch.sink == Some(sink))
+            .map(|branch| branch.sink_buffer)
+    }
+
+    /// Branch leaving the machine port `start`
+    pub fn branch_from(&self, start: Entity) -> Option<&Branch> {
+        self.branches.iter().find(|branch| branch.start == start)
+    }
+}
+
 #[derive(Event)]
 pub struct InvalidateNetworks;
 
@@ -48,7 +83,9 @@ fn network_rebuild(
     mut commands: Commands,
     mut invalidations: EventReader<InvalidateNetworks>,
     networks: Query<Entity, With<PipeNetwork>>,
-    machines: Query<(&Buffer, &Children), (With<Machine>, With<TileCoords>)>,
+    machines: Query<(), (With<Machine>, With<TileCoords>)>,
+    splitters: Query<(Entity, &Children), (With<Splitter>, With<TileCoords>)>,
+    mut buffers: Query<&mut Buffer>,
     ports: Query<(Entity, &ChildOf, &MachinePort)>,
     pipes: Query<(&Pipe, &TileCoords)>,
 ) {
@@ -61,16 +98,9 @@ fn network_rebuild(
         commands.entity(network).despawn();
     }
     info!("Rebuilding pipe networks");
-    let mut networks = vec![];
-    for (port_entity, child_of, port) in ports.iter() {
-        if port.flow == FlowDirection::Inlet {
-            continue;
-        }
-        let parent = child_of.parent();
-        if !machines.contains(parent) {
-            continue;
-        }
-        let (buffer, _) = machines.get(child_of.parent()).unwrap();
+
+    // follows the pipes leaving `port` to the machine port they end at
+    let trace = |port: &MachinePort, start: Entity| {
         let mut sink = None;
         let mut members = vec![];
         if let Some(connected) = port.connected {
@@ -90,33 +120,188 @@ fn network_rebuild(
                         sink = Some(last);
                     }
                 };
-            } else if let Ok((nbr_port, _, _)) = ports.get(connected) {
+            } else if ports.contains(connected) {
                 // neighbor is machine
-                sink = Some(nbr_port);
-                members.push(nbr_port);
+                sink = Some(connected);
+            }
+        }
+        let pipes = members
+            .into_iter()
+            .filter(|member| pipes.contains(*member))
+            .collect::<Vec<_>>();
+        Branch {
+            start,
+            sink_buffer: pipes.last().copied().unwrap_or(start),
+            pipes,
+            sink,
+        }
+    };
+    // splitters are part of the network feeding them, their outlets start new branches
+    let branches_from = |roots: Vec<Entity>, split: &mut HashSet<Entity>| {
+        let mut branches = vec![];
+        let mut open = roots;
+        open.reverse();
+        while let Some(start_port) = open.pop() {
+            let Ok((_, child_of, port)) = ports.get(start_port) else {
+                continue;
+            };
+            let start = if buffers.contains(start_port) {
+                start_port
+            } else {
+                child_of.parent()
+            };
+            let branch = trace(port, start);
+            let splitter = branch
+                .sink
+                .and_then(|sink| ports.get(sink).ok())
+                .map(|(_, child_of, _)| child_of.parent())
+                .filter(|machine| splitters.contains(*machine));
+            if let Some(splitter) = splitter.filter(|splitter| split.insert(*splitter)) {
+                open.extend(splitter_outlets(splitter, &splitters, &ports));
             }
+            branches.push(branch);
         }
-        members.push(port_entity);
+        branches
+    };
 
-        networks.push((port_entity, buffer.0, sink, members));
+    let mut split = HashSet::new();
+    let mut networks = vec![];
+    for (port_entity, child_of, port) in ports.iter() {
+        if port.flow == FlowDirection::Inlet {
+            continue;
+        }
+        let parent = child_of.parent();
+        if !machines.contains(parent) || splitters.contains(parent) {
+            continue;
+        }
+        let source_buffer = if buffers.contains(port_entity) {
+            port_entity
+        } else {
+            parent
+        };
+        let Ok(resource) = buffers.get(source_buffer).map(|buffer| buffer.0) else {
+            continue;
+        };
+        networks.push((
+            port_entity,
+            resource,
+            branches_from(vec![port_entity], &mut split),
+        ));
+    }
+    // splitters nothing flows into still pass on what their outlets hold
+    for (splitter, children) in splitters.iter() {
+        if split.contains(&splitter) {
+            continue;
+        }
+        let Some(inlet) = children.iter().find(|child| {
+            ports
+                .get(*child)
+                .is_ok_and(|(_, _, port)| port.flow == FlowDirection::Inlet)
+        }) else {
+            continue;
+        };
+        let outlets = splitter_outlets(splitter, &splitters, &ports);
+        let resource = outlets
+            .iter()
+            .filter_map(|outlet| buffers.get(*outlet).ok())
+            .find(|buffer| buffer.1 > 0.0)
+            .map_or(ResourceType::Mineral, |buffer| buffer.0);
+        split.insert(splitter);
+        networks.push((inlet, resource, branches_from(outlets, &mut split)));
     }
 
-    for (source, resource, sink, members) in networks {
+    for (source, resource, branches) in networks {
+        for branch in branches.iter() {
+            let in_transit = branch.pipes.iter().copied().chain(
+                // items waiting in a splitter's outlets are in transit as well
+                Some(branch.start).filter(|start| ports.contains(*start)),
+            );
+            for entity in in_transit {
+                // whatever was in transit can't mix with the new resource, so it spills out
+                if let Ok(mut contents) = buffers.get_mut(entity) {
+                    if contents.0 != resource {
+                        *contents = Buffer(resource, 0.0);
+                    }
+                }
+            }
+        }
+
+        let ports_in_network = branches
+            .iter()
+            .flat_map(|branch| [Some(branch.start), branch.sink])
+            .flatten()
+            .filter(|entity| ports.contains(*entity))
+            .chain([source])
+            .collect::<HashSet<_>>();
+        let members = branches
+            .iter()
+            .flat_map(|branch| branch.pipes.iter().copied())
+            .chain(ports_in_network.iter().copied())
+            .collect::<Vec<_>>();
         let network = commands
             .spawn((
                 Name::new(format!("{} Network", resource.to_string())),
                 PipeNetwork {
                     source,
+                    branches,
                     resource,
-                    sink,
                     material: Handle::default(),
                 },
-                NetworkMembers(members.clone()),
+                NetworkMembers(members),
             ))
             .id();
-        commands.entity(source).insert(InNetwork(network));
-        if let Some(sink) = sink {
-            commands.entity(sink).insert(InNetwork(network));
+        for port in ports_in_network {
+            commands.entity(port).insert(InNetwork(network));
+        }
+    }
+}
+
+fn splitter_outlets(
+    splitter: Entity,
+    splitters: &Query<(Entity, &Children), (With<Splitter>, With<TileCoords>)>,
+    ports: &Query<(Entity, &ChildOf, &MachinePort)>,
+) -> Vec<Entity> {
+    splitters
+        .get(splitter)
+        .into_iter()
+        .flat_map(|(_, children)| children.iter())
+        .filter(|child| {
+            ports
+                .get(*child)
+                .is_ok_and(|(_, _, port)| port.flow == FlowDirection::Outlet)
+        })
+        .collect()
+}
+
+/// Moves items one pipe further along each branch per factory tick, starting at the sink
+/// so every item only advances once.
+fn network_flow(
+    mut ticks: EventReader<FactoryTick>,
+    networks: Query<&PipeNetwork>,
+    mut buffers: Query<&mut Buffer>,
+) {
+    for _ in ticks.read() {
+        for branch in networks.iter().flat_map(|network| network.branches.iter()) {
+            let chain = [branch.start]
+                .into_iter()
+                .chain(branch.pipes.iter().copied())
+                .collect::<Vec<_>>();
+            for pair in chain.windows(2).rev() {
+                let Ok([mut from, mut to]) = buffers.get_many_mut([pair[0], pair[1]]) else {
+                    continue;
+                };
+                if from.0 != to.0 {
+                    if to.1 > 0.0 {
+                        continue;
+                    }
+                    to.0 = from.0;
+                }
+                let amount = PIPE_THROUGHPUT.min(from.1).min(PIPE_CAPACITY - to.1);
+                if amount > 0.0 {
+                    from.1 -= amount;
+                    to.1 += amount;
+                }
+            }
         }
     }
 }
@@ -128,12 +313,16 @@ fn network_debug(
 ) -> Result {
     for network in networks.iter() {
         let source = names.get(child_of.get(network.source)?.parent())?;
-        let sink = if let Some(sink) = network.sink {
-            names.get(child_of.get(sink)?.parent())?
+        let sinks = network
+            .sinks()
+            .map(|sink| Ok(names.get(child_of.get(sink)?.parent())?.as_str()))
+            .collect::<Result<Vec<_>>>()?;
+        let sinks = if sinks.is_empty() {
+            "None".to_string()
         } else {
-            &Name::new("None")
+            sinks.join(", ")
         };
-        info!("Pipe network: {} -> {}", source.as_str(), sink.as_str());
+        info!("Pipe network: {} -> {}", source.as_str(), sinks);
     }
 
     Ok(())
@@ -0,0 +1,490 @@
+use bevy::{
+    ecs::system::SystemParam,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{materials::DitherMaterial, resources::Resources, scheduling::Sets};
+
+use super::{
+    grid::{grid_spawn, grid_spawn_logic, Direction, Grid, TileCoords},
+    machines::{
+        machine_port_logic, rotate_machine, rotated_machine_port, Buffer, FlowDirection, Inlet,
+        MachinePort, MachineRotation, Outlet, SplitMode, Splitter,
+    },
+    pipe::{pipe_bundle, pipe_logic, Pipe, PipeFlowMaterial},
+    pipe_network::InvalidateNetworks,
+    shop::{spawn_shop_item, spawn_shop_item_logic, Cost, ShopItem},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    simulation_plugin(app);
+    app.add_systems(Update, layout_input.in_set(Sets::Input));
+}
+
+pub(super) fn simulation_plugin(app: &mut App) {
+    app.init_resource::<LayoutPath>()
+        .add_observer(layout_save)
+        .add_observer(layout_load);
+}
+
+/// Environment variable that changes where the layout is saved
+const LAYOUT_VAR: &str = "SPACETIME_LAYOUT";
+const DEFAULT_LAYOUT_PATH: &str = "factory.ron";
+/// Bump this whenever [`FactoryLayout`] changes in a way old files can't be read.
+pub const LAYOUT_VERSION: u32 = 1;
+
+/// Everything the player has built on the grid. Inlets and outlets are part of the
+/// default grid and are not saved.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct FactoryLayout {
+    pub version: u32,
+    pub pipes: Vec<SavedPipe>,
+    pub machines: Vec<SavedMachine>,
+}
+
+impl FactoryLayout {
+    /// What building every machine in the layout costs
+    pub fn cost(&self) -> Cost {
+        self.machines
+            .iter()
+            .map(|machine| machine.item.cost())
+            .sum()
+    }
+
+    /// Tiles taken by the saved pipes and machines
+    fn coords(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.pipes
+            .iter()
+            .map(|pipe| pipe.coords)
+            .chain(self.machines.iter().map(|machine| machine.coords))
+    }
+
+    /// Same buildings with nothing in them
+    pub fn emptied(mut self) -> Self {
+        for pipe in self.pipes.iter_mut() {
+            pipe.buffer = None;
+        }
+        for machine in self.machines.iter_mut() {
+            machine.buffer.1 = 0.0;
+            for port in machine.ports.iter_mut() {
+                port.buffer = None;
+            }
+        }
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedPipe {
+    pub coords: IVec2,
+    /// Side of the pipe that `Pipe::to` points at
+    pub to: Option<Direction>,
+    /// Side of the pipe that `Pipe::from` points at
+    pub from: Option<Direction>,
+    /// Items in transit
+    #[serde(default)]
+    pub buffer: Option<Buffer>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedMachine {
+    pub item: ShopItem,
+    pub coords: IVec2,
+    pub buffer: Buffer,
+    pub ports: Vec<SavedPort>,
+    #[serde(default)]
+    pub split_mode: Option<SplitMode>,
+    /// Quarter turns counter-clockwise, see [`MachineRotation`]
+    #[serde(default)]
+    pub rotation: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedPort {
+    pub side: Direction,
+    pub flow: FlowDirection,
+    /// Whether the port is connected to whatever is on its side
+    pub connected: bool,
+    /// Items held by the port itself, like the outlets of a splitter
+    #[serde(default)]
+    pub buffer: Option<Buffer>,
+}
+
+/// File the factory layout is saved to and loaded from, `factory.ron` unless
+/// `SPACETIME_LAYOUT` is set.
+#[derive(Resource, Clone, Debug)]
+pub struct LayoutPath(pub String);
+
+impl Default for LayoutPath {
+    fn default() -> Self {
+        Self(std::env::var(LAYOUT_VAR).unwrap_or_else(|_| DEFAULT_LAYOUT_PATH.to_string()))
+    }
+}
+
+#[derive(Event)]
+pub struct SaveFactoryLayout;
+
+#[derive(Event)]
+pub struct LoadFactoryLayout;
+
+/// A saved layout replaced whatever was on the grid
+#[derive(Event)]
+pub struct FactoryLayoutLoaded;
+
+fn layout_input(mut commands: Commands, keyboard_input: Res<ButtonInput<KeyCode>>) {
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        commands.trigger(SaveFactoryLayout);
+    }
+    if keyboard_input.just_pressed(KeyCode::F9) {
+        commands.trigger(LoadFactoryLayout);
+    }
+}
+
+/// Reads the current [`FactoryLayout`] off the grid.
+#[derive(SystemParam)]
+pub struct LayoutReader<'w, 's> {
+    grid: Res<'w, Grid>,
+    pipes: Query<'w, 's, (&'static Pipe, Option<&'static Buffer>)>,
+    machines: Query<
+        'w,
+        's,
+        (
+            &'static ShopItem,
+            &'static Buffer,
+            &'static MachineRotation,
+            &'static Children,
+            Option<&'static Splitter>,
+        ),
+        With<TileCoords>,
+    >,
+    ports: Query<'w, 's, (&'static MachinePort, Option<&'static Buffer>)>,
+    coords: Query<'w, 's, &'static TileCoords>,
+    child_of: Query<'w, 's, &'static ChildOf>,
+}
+
+impl LayoutReader<'_, '_> {
+    pub fn layout(&self) -> FactoryLayout {
+        let direction_to = |pos: IVec2, target: Entity| {
+            let building = self.child_of.get(target).map_or(target, |c| c.parent());
+            self.coords
+                .get(building)
+                .ok()
+                .map(|target_coords| TileCoords(pos).direction_to(target_coords))
+        };
+
+        let mut layout = FactoryLayout {
+            version: LAYOUT_VERSION,
+            pipes: vec![],
+            machines: vec![],
+        };
+        for (pos, building) in self.grid.buildings() {
+            if let Ok((pipe, buffer)) = self.pipes.get(building) {
+                layout.pipes.push(SavedPipe {
+                    coords: pos,
+                    to: pipe.to.and_then(|to| direction_to(pos, to)),
+                    from: pipe.from.and_then(|from| direction_to(pos, from)),
+                    buffer: buffer.cloned(),
+                });
+            } else if let Ok((item, buffer, rotation, children, splitter)) =
+                self.machines.get(building)
+            {
+                layout.machines.push(SavedMachine {
+                    item: *item,
+                    coords: pos,
+                    buffer: buffer.clone(),
+                    ports: children
+                        .iter()
+                        .filter_map(|child| self.ports.get(child).ok())
+                        .map(|(port, buffer)| SavedPort {
+                            side: port.side,
+                            flow: port.flow.clone(),
+                            connected: port.connected.is_some(),
+                            buffer: buffer.cloned(),
+                        })
+                        .collect(),
+                    split_mode: splitter.map(|splitter| splitter.mode),
+                    rotation: rotation.0,
+                });
+            }
+        }
+        layout
+    }
+}
+
+fn layout_save(_trigger: Trigger<SaveFactoryLayout>, reader: LayoutReader, path: Res<LayoutPath>) {
+    let serialized = match ron::ser::to_string_pretty(&reader.layout(), default()) {
+        Ok(serialized) => serialized,
+        Err(err) => {
+            warn!("Failed to serialize factory layout: {err}");
+            return;
+        }
+    };
+    match std::fs::write(&path.0, serialized) {
+        Ok(()) => info!("Saved factory layout to {}", path.0),
+        Err(err) => warn!("Failed to write factory layout to {}: {err}", path.0),
+    }
+}
+
+/// Loading pays for the machines it adds and refunds the ones it removes, like undoing does.
+/// Everything is rebuilt empty, contents in the file are ignored since they were never paid
+/// for.
+fn layout_load(
+    _trigger: Trigger<LoadFactoryLayout>,
+    mut commands: Commands,
+    grid: Res<Grid>,
+    fixed: Query<&TileCoords, Or<(With<Inlet>, With<Outlet>)>>,
+    path: Res<LayoutPath>,
+    reader: LayoutReader,
+    mut resources: ResMut<Resources>,
+) {
+    let serialized = match std::fs::read_to_string(&path.0) {
+        Ok(serialized) => serialized,
+        Err(err) => {
+            warn!("Failed to read factory layout from {}: {err}", path.0);
+            return;
+        }
+    };
+    let layout = match ron::from_str::<FactoryLayout>(&serialized) {
+        Ok(layout) => layout,
+        Err(err) => {
+            warn!("Failed to parse factory layout: {err}");
+            return;
+        }
+    };
+    if layout.version != LAYOUT_VERSION {
+        warn!(
+            "Factory layout has version {}, expected {}",
+            layout.version, LAYOUT_VERSION
+        );
+        return;
+    }
+    let mut taken = fixed.iter().map(|coords| coords.0).collect::<HashSet<_>>();
+    if let Some(coords) = layout
+        .coords()
+        .find(|&coords| grid.get_tile(coords).is_none() || !taken.insert(coords))
+    {
+        warn!("Factory layout has a building off the grid or on a taken tile at {coords}");
+        return;
+    }
+
+    let cost = layout.cost() - reader.layout().cost();
+    if !cost.affordable(&resources) {
+        info!(
+            "Can't afford to load factory layout, {}",
+            cost.description()
+        );
+        return;
+    }
+    cost.pay(&mut resources);
+
+    info!("Loading factory layout from {}", path.0);
+    apply_layout(&mut commands, &grid, layout.emptied());
+    commands.trigger(FactoryLayoutLoaded);
+}
+
+/// Tears down the grid and rebuilds it from `layout`.
+pub fn apply_layout(commands: &mut Commands, grid: &Grid, layout: FactoryLayout) {
+    commands.run_system_cached(layout_empty_inlets);
+    commands.entity(grid.entity).despawn();
+    commands.run_system_cached(layout_spawn_grid);
+    commands.run_system_cached_with(layout_spawn_buildings, layout.clone());
+    commands.run_system_cached_with(layout_connect, layout);
+}
+
+/// Meshes and materials for the rebuilt buildings, headless simulations don't have any.
+#[derive(SystemParam)]
+struct LayoutVisuals<'w> {
+    meshes: Option<ResMut<'w, Assets<Mesh>>>,
+    materials: Option<ResMut<'w, Assets<DitherMaterial>>>,
+    flow_material: Option<Res<'w, PipeFlowMaterial>>,
+}
+
+impl LayoutVisuals<'_> {
+    fn spawn_pipe(&self, commands: &mut Commands, pos: IVec2) -> Entity {
+        if self.flow_material.is_some() {
+            commands.spawn(pipe_bundle(pos)).id()
+        } else {
+            commands.spawn(pipe_logic(pos)).id()
+        }
+    }
+
+    fn spawn_machine(&mut self, commands: &mut Commands, item: ShopItem) -> Entity {
+        match (&mut self.meshes, &mut self.materials, &self.flow_material) {
+            (Some(meshes), Some(materials), Some(flow_material)) => {
+                spawn_shop_item(commands, item, meshes, materials, flow_material.0.clone())
+            }
+            _ => spawn_shop_item_logic(commands, item),
+        }
+    }
+
+    fn insert_port(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        port: MachinePort,
+        rotation: MachineRotation,
+    ) {
+        if let Some(flow_material) = &self.flow_material {
+            commands.entity(entity).insert(rotated_machine_port(
+                port,
+                rotation,
+                flow_material.0.clone(),
+            ));
+        } else {
+            commands.entity(entity).insert(machine_port_logic(port));
+        }
+    }
+}
+
+/// The inlets are rebuilt empty, whatever they held goes back into [`Resources`].
+fn layout_empty_inlets(inlets: Query<&Buffer, With<Inlet>>, mut resources: ResMut<Resources>) {
+    for buffer in inlets.iter() {
+        resources.add(buffer.0, buffer.1);
+    }
+}
+
+fn layout_spawn_grid(mut commands: Commands, visuals: LayoutVisuals) {
+    if visuals.flow_material.is_some() {
+        commands.run_system_cached(grid_spawn);
+    } else {
+        commands.run_system_cached(grid_spawn_logic);
+    }
+}
+
+fn layout_spawn_buildings(
+    In(layout): In<FactoryLayout>,
+    mut commands: Commands,
+    mut grid: ResMut<Grid>,
+    mut visuals: LayoutVisuals,
+) {
+    for pipe in layout.pipes {
+        let entity = visuals.spawn_pipe(&mut commands, pipe.coords);
+        commands.entity(entity).insert(ChildOf(grid.entity));
+        if let Some(buffer) = pipe.buffer {
+            commands.entity(entity).insert(buffer);
+        }
+        grid.insert_building(pipe.coords, entity);
+    }
+    for machine in layout.machines {
+        let entity = visuals.spawn_machine(&mut commands, machine.item);
+        commands
+            .entity(entity)
+            .insert((
+                TileCoords(machine.coords),
+                ChildOf(grid.entity),
+                machine.buffer,
+            ))
+            .queue(rotate_machine(machine.rotation as i32));
+        if let Some(mode) = machine.split_mode {
+            commands.entity(entity).insert(Splitter::new(mode));
+        }
+        grid.insert_building(machine.coords, entity);
+    }
+}
+
+/// Replaces the default ports of the loaded machines with the saved ones, then restores
+/// every pipe and port connection. Since the saved layout was already fully connected,
+/// the connection systems won't change anything when they see these afterwards.
+fn layout_connect(
+    In(layout): In<FactoryLayout>,
+    mut commands: Commands,
+    grid: Res<Grid>,
+    children: Query<&Children>,
+    pipes: Query<(), With<Pipe>>,
+    mut ports: Query<&mut MachinePort>,
+    visuals: LayoutVisuals,
+    mut invalidate: EventWriter<InvalidateNetworks>,
+) {
+    let loaded = layout
+        .machines
+        .iter()
+        .map(|machine| machine.coords)
+        .collect::<HashSet<_>>();
+    let mut new_ports = HashMap::new();
+    for machine in layout.machines.iter() {
+        let Some(entity) = grid.get_building(machine.coords) else {
+            continue;
+        };
+        for child in children.get(entity).into_iter().flatten() {
+            if ports.contains(*child) {
+                commands.entity(*child).despawn();
+            }
+        }
+        for port in machine.ports.iter() {
+            new_ports.insert((machine.coords, port.side), commands.spawn_empty().id());
+        }
+    }
+
+    let port_at = |pos: IVec2, side: Direction| {
+        if loaded.contains(&pos) {
+            return new_ports.get(&(pos, side)).copied();
+        }
+        let building = grid.get_building(pos)?;
+        children
+            .get(building)
+            .ok()?
+            .iter()
+            .find(|child| ports.get(*child).is_ok_and(|port| port.side == side))
+    };
+    let neighbor = |pos: IVec2, dir: Direction| {
+        let nbr_pos = pos + dir.as_ivec2();
+        let building = grid.get_building(nbr_pos)?;
+        if pipes.contains(building) {
+            Some(building)
+        } else {
+            port_at(nbr_pos, dir.flip())
+        }
+    };
+
+    // ports of the fixed inlets and outlets aren't respawned, so they are updated in place
+    let mut existing_connections = vec![];
+    for pipe in layout.pipes.iter() {
+        let Some(entity) = grid.get_building(pipe.coords) else {
+            continue;
+        };
+        let to = pipe.to.and_then(|dir| neighbor(pipe.coords, dir));
+        let from = pipe.from.and_then(|dir| neighbor(pipe.coords, dir));
+        for target in [to, from].into_iter().flatten() {
+            existing_connections.push((target, entity));
+        }
+        commands.entity(entity).insert(Pipe { to, from });
+    }
+    for machine in layout.machines.iter() {
+        let Some(entity) = grid.get_building(machine.coords) else {
+            continue;
+        };
+        for port in machine.ports.iter() {
+            let port_entity = new_ports[&(machine.coords, port.side)];
+            let connected = port
+                .connected
+                .then(|| neighbor(machine.coords, port.side))
+                .flatten();
+            if let Some(connected) = connected {
+                existing_connections.push((connected, port_entity));
+            }
+            visuals.insert_port(
+                &mut commands,
+                port_entity,
+                MachinePort {
+                    side: port.side,
+                    flow: port.flow.clone(),
+                    connected,
+                },
+                MachineRotation(machine.rotation),
+            );
+            commands.entity(port_entity).insert(ChildOf(entity));
+            if let Some(buffer) = port.buffer.clone() {
+                commands.entity(port_entity).insert(buffer);
+            }
+        }
+    }
+    for (target, connected) in existing_connections {
+        if let Ok(mut port) = ports.get_mut(target) {
+            port.connected = Some(connected);
+        }
+    }
+
+    invalidate.write(InvalidateNetworks);
+}
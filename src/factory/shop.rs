@@ -1,39 +1,201 @@
-use bevy::prelude::*;
+use bevy::{input::mouse::MouseWheel, prelude::*};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    layers::FactoryLayer, materials::DitherMaterial, scheduling::Sets, sounds::Sounds,
-    z_order::ZOrder, SCREEN_SIZE,
+    layers::FactoryLayer,
+    materials::{DitherMaterial, MetalDither, SOLID_WHITE},
+    resources::{ResourceType, Resources},
+    scheduling::Sets,
+    sounds::Sounds,
+    z_order::ZOrder,
+    SCREEN_SIZE,
 };
 
 use super::{
     camera::CursorPosition,
     grid::{Grid, TileCoords, TILE_SIZE},
-    machines::{ammo_factory, hull_fixer, pipe_switch, rocket_factory},
+    machines::{
+        ammo_factory, ammo_factory_logic, generator, generator_logic, hull_fixer, hull_fixer_logic,
+        merger, merger_logic, pipe_switch, pipe_switch_logic, refinery, refinery_logic,
+        rocket_factory, rocket_factory_logic, rotate_machine, splitter, splitter_logic,
+        weapon_workshop, weapon_workshop_logic, MachineRotation,
+    },
     pipe::PipeFlowMaterial,
     pipe_network::InvalidateNetworks,
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(Startup, shop_spawn.in_set(Sets::Spawn))
-        .add_systems(Update, shop_item_drag.in_set(Sets::Input))
-        .add_observer(shop_item_observers)
-        .add_observer(shop_layout);
+    app.add_systems(
+        Startup,
+        (
+            shop_setup_materials.in_set(Sets::Init),
+            shop_spawn.in_set(Sets::Spawn),
+        ),
+    )
+    .add_systems(
+        Update,
+        (
+            (shop_item_drag, shop_item_rotate).in_set(Sets::Input),
+            shop_item_grey_out.in_set(Sets::PostUpdate),
+        ),
+    )
+    .add_observer(shop_item_observers)
+    .add_observer(shop_layout);
 }
 
 #[derive(Resource)]
 pub struct Shop(pub Entity);
 
-#[derive(Component)]
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum ShopItem {
     AmmoFactory,
     PipeSwitch,
     HullFixer,
     RocketFactory,
+    Splitter,
+    Merger,
+    WeaponWorkshop,
+    Generator,
+    Refinery,
+}
+
+impl ShopItem {
+    pub fn cost(&self) -> Cost {
+        match self {
+            ShopItem::AmmoFactory => Cost::minerals(5.0),
+            ShopItem::PipeSwitch => Cost::minerals(2.0),
+            ShopItem::HullFixer => Cost {
+                time: 5.0,
+                ..Cost::minerals(8.0)
+            },
+            ShopItem::RocketFactory => Cost {
+                gas: 5.0,
+                ..Cost::minerals(10.0)
+            },
+            ShopItem::Splitter => Cost::minerals(3.0),
+            ShopItem::Merger => Cost::minerals(3.0),
+            ShopItem::WeaponWorkshop => Cost {
+                time: 10.0,
+                ..Cost::minerals(15.0)
+            },
+            ShopItem::Generator => Cost {
+                gas: 3.0,
+                ..Cost::minerals(8.0)
+            },
+            ShopItem::Refinery => Cost {
+                gas: 2.0,
+                ..Cost::minerals(10.0)
+            },
+        }
+    }
+}
+
+/// Share of the cost given back when a built machine is thrown away
+pub const REFUND_RATE: f32 = 0.5;
+
+/// Resources it takes to build a machine
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Cost {
+    pub minerals: f32,
+    pub gas: f32,
+    pub time: f32,
+}
+
+impl Cost {
+    pub fn minerals(minerals: f32) -> Self {
+        Self {
+            minerals,
+            ..default()
+        }
+    }
+
+    fn parts(&self) -> [(ResourceType, f32); 3] {
+        [
+            (ResourceType::Mineral, self.minerals),
+            (ResourceType::Gas, self.gas),
+            (ResourceType::Time, self.time),
+        ]
+    }
+
+    pub fn affordable(&self, resources: &Resources) -> bool {
+        self.parts()
+            .iter()
+            .all(|(resource, amount)| resources.get(*resource) >= *amount)
+    }
+
+    /// Takes the cost out of `resources`. Negative parts are given back.
+    pub fn pay(&self, resources: &mut Resources) {
+        for (resource, amount) in self.parts() {
+            resources.add(resource, -amount);
+        }
+    }
+
+    pub fn scale(&self, factor: f32) -> Self {
+        Self {
+            minerals: self.minerals * factor,
+            gas: self.gas * factor,
+            time: self.time * factor,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        let parts = self
+            .parts()
+            .iter()
+            .filter(|(_, amount)| *amount > 0.0)
+            .map(|(resource, amount)| format!("{amount} {}", resource.to_string()))
+            .collect::<Vec<_>>();
+        if parts.is_empty() {
+            "Free".to_string()
+        } else {
+            format!("Cost: {}", parts.join(", "))
+        }
+    }
+}
+
+impl std::ops::Sub for Cost {
+    type Output = Cost;
+
+    fn sub(self, other: Cost) -> Cost {
+        Cost {
+            minerals: self.minerals - other.minerals,
+            gas: self.gas - other.gas,
+            time: self.time - other.time,
+        }
+    }
+}
+
+impl std::iter::Sum for Cost {
+    fn sum<I: Iterator<Item = Cost>>(iter: I) -> Cost {
+        iter.fold(Cost::default(), |total, cost| Cost {
+            minerals: total.minerals + cost.minerals,
+            gas: total.gas + cost.gas,
+            time: total.time + cost.time,
+        })
+    }
 }
 
 #[derive(Component)]
 pub struct ShopOrder(pub usize);
 
+/// Shop item that the player can't pay for right now
+#[derive(Component)]
+pub struct Unaffordable;
+
+#[derive(Resource)]
+struct ShopMaterials {
+    unaffordable: Handle<DitherMaterial>,
+}
+
+fn shop_setup_materials(mut commands: Commands, mut materials: ResMut<Assets<DitherMaterial>>) {
+    commands.insert_resource(ShopMaterials {
+        unaffordable: materials.add(MetalDither {
+            fill: 0.3,
+            scale: 40.0,
+        }),
+    });
+}
+
 #[derive(Resource)]
 pub struct PickedUpItem(pub Entity);
 
@@ -66,6 +228,20 @@ fn shop_spawn(
                 (
                     ShopOrder(3),
                     rocket_factory(&mut meshes, &mut materials, flow_material.0.clone()),
+                ),
+                (ShopOrder(4), splitter(&mut meshes, flow_material.0.clone()),),
+                (ShopOrder(5), merger(&mut meshes, flow_material.0.clone()),),
+                (
+                    ShopOrder(6),
+                    weapon_workshop(&mut meshes, &mut materials, flow_material.0.clone()),
+                ),
+                (
+                    ShopOrder(7),
+                    generator(&mut meshes, &mut materials, flow_material.0.clone()),
+                ),
+                (
+                    ShopOrder(8),
+                    refinery(&mut meshes, &mut materials, flow_material.0.clone()),
                 )
             ],
         ))
@@ -101,55 +277,60 @@ fn shop_item_drag_end(
     mut commands: Commands,
     mut invalidate: EventWriter<InvalidateNetworks>,
     cursor_pos: CursorPosition,
-    shop_items: Query<(&ShopItem, Option<&TileCoords>)>,
+    shop_items: Query<(&ShopItem, Option<&TileCoords>, &MachineRotation)>,
     mut grid: ResMut<Grid>,
     shop: Res<Shop>,
     sounds: Res<Sounds>,
     flow_material: Res<PipeFlowMaterial>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<DitherMaterial>>,
+    mut resources: ResMut<Resources>,
 ) -> Result {
     let target = trigger.target();
-    let (shop_item, coords) = shop_items.get(target)?;
+    let (shop_item, coords, rotation) = shop_items.get(target)?;
+    let cost = shop_item.cost();
     commands.remove_resource::<PickedUpItem>();
+    let mut placed = false;
     if let Some(tile_pos) = cursor_pos.tile() {
         info!("Dropped building on position: {:?}", tile_pos);
-        if grid.get_tile(tile_pos).is_some() && grid.get_building(tile_pos).is_none() {
-            let spawned = match shop_item {
-                ShopItem::AmmoFactory => commands
-                    .spawn(ammo_factory(&mut materials, flow_material.0.clone()))
-                    .id(),
-                ShopItem::PipeSwitch => commands
-                    .spawn(pipe_switch(&mut meshes, flow_material.0.clone()))
-                    .id(),
-                ShopItem::HullFixer => commands
-                    .spawn(hull_fixer(
-                        &mut meshes,
-                        &mut materials,
-                        flow_material.0.clone(),
-                    ))
-                    .id(),
-                ShopItem::RocketFactory => commands
-                    .spawn(rocket_factory(
-                        &mut meshes,
-                        &mut materials,
-                        flow_material.0.clone(),
-                    ))
-                    .id(),
-            };
+        // moving a built machine is free
+        let affordable = coords.is_some() || cost.affordable(&resources);
+        if !affordable {
+            info!("Can't afford {:?}, {}", shop_item, cost.description());
+        }
+        if affordable && grid.get_tile(tile_pos).is_some() && grid.get_building(tile_pos).is_none()
+        {
+            if coords.is_none() {
+                cost.pay(&mut resources);
+            }
+            placed = true;
+            let spawned = spawn_shop_item(
+                &mut commands,
+                *shop_item,
+                &mut meshes,
+                &mut materials,
+                flow_material.0.clone(),
+            );
             grid.insert_building(tile_pos, spawned);
             commands
                 .entity(spawned)
-                .insert((TileCoords(tile_pos), ChildOf(grid.entity)));
+                .insert((TileCoords(tile_pos), ChildOf(grid.entity)))
+                .queue(rotate_machine(rotation.0 as i32));
         }
     }
     if let Some(coords) = coords {
-        // thrown away built item
+        // moved or thrown away built item
         commands.entity(target).despawn();
         grid.remove_building(coords.0);
+        if !placed {
+            cost.scale(-REFUND_RATE).pay(&mut resources);
+        }
     } else {
         // return to shop
-        commands.entity(target).insert(ChildOf(shop.0));
+        commands
+            .entity(target)
+            .insert(ChildOf(shop.0))
+            .queue(rotate_machine(-(rotation.0 as i32)));
         commands.trigger(InvalidateShopLayout);
     }
     invalidate.write(InvalidateNetworks);
@@ -162,6 +343,51 @@ fn shop_item_drag_end(
     Ok(())
 }
 
+pub fn spawn_shop_item(
+    commands: &mut Commands,
+    shop_item: ShopItem,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<DitherMaterial>>,
+    flow_material: Handle<DitherMaterial>,
+) -> Entity {
+    match shop_item {
+        ShopItem::AmmoFactory => commands.spawn(ammo_factory(materials, flow_material)).id(),
+        ShopItem::PipeSwitch => commands.spawn(pipe_switch(meshes, flow_material)).id(),
+        ShopItem::HullFixer => commands
+            .spawn(hull_fixer(meshes, materials, flow_material))
+            .id(),
+        ShopItem::RocketFactory => commands
+            .spawn(rocket_factory(meshes, materials, flow_material))
+            .id(),
+        ShopItem::Splitter => commands.spawn(splitter(meshes, flow_material)).id(),
+        ShopItem::Merger => commands.spawn(merger(meshes, flow_material)).id(),
+        ShopItem::WeaponWorkshop => commands
+            .spawn(weapon_workshop(meshes, materials, flow_material))
+            .id(),
+        ShopItem::Generator => commands
+            .spawn(generator(meshes, materials, flow_material))
+            .id(),
+        ShopItem::Refinery => commands
+            .spawn(refinery(meshes, materials, flow_material))
+            .id(),
+    }
+}
+
+/// Same as [`spawn_shop_item`], but without any meshes or materials.
+pub fn spawn_shop_item_logic(commands: &mut Commands, shop_item: ShopItem) -> Entity {
+    match shop_item {
+        ShopItem::AmmoFactory => commands.spawn(ammo_factory_logic()).id(),
+        ShopItem::PipeSwitch => commands.spawn(pipe_switch_logic()).id(),
+        ShopItem::HullFixer => commands.spawn(hull_fixer_logic()).id(),
+        ShopItem::RocketFactory => commands.spawn(rocket_factory_logic()).id(),
+        ShopItem::Splitter => commands.spawn(splitter_logic()).id(),
+        ShopItem::Merger => commands.spawn(merger_logic()).id(),
+        ShopItem::WeaponWorkshop => commands.spawn(weapon_workshop_logic()).id(),
+        ShopItem::Generator => commands.spawn(generator_logic()).id(),
+        ShopItem::Refinery => commands.spawn(refinery_logic()).id(),
+    }
+}
+
 fn shop_item_drag(
     mut shop_items: Query<&mut Transform, With<ShopItem>>,
     picked_up_item: Option<Res<PickedUpItem>>,
@@ -179,6 +405,55 @@ fn shop_item_drag(
     }
 }
 
+fn shop_item_rotate(
+    mut commands: Commands,
+    picked_up_item: Option<Res<PickedUpItem>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut scroll: EventReader<MouseWheel>,
+) {
+    let scrolled = scroll.read().map(|event| event.y).sum::<f32>();
+    let Some(picked_up_item) = picked_up_item else {
+        return;
+    };
+    let mut turns = 0;
+    if keyboard_input.just_pressed(KeyCode::KeyQ) || scrolled > 0.0 {
+        turns += 1;
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyE) || scrolled < 0.0 {
+        turns -= 1;
+    }
+    if turns != 0 {
+        commands
+            .entity(picked_up_item.0)
+            .queue(rotate_machine(turns));
+    }
+}
+
+fn shop_item_grey_out(
+    mut commands: Commands,
+    shop_items: Query<(Entity, &ShopItem, Has<Unaffordable>), Without<TileCoords>>,
+    resources: Res<Resources>,
+    shop_materials: Res<ShopMaterials>,
+) {
+    for (entity, item, greyed_out) in shop_items.iter() {
+        let affordable = item.cost().affordable(&resources);
+        if affordable && greyed_out {
+            commands
+                .entity(entity)
+                .remove::<(Unaffordable, MeshMaterial2d<DitherMaterial>)>()
+                .insert(MeshMaterial2d(SOLID_WHITE));
+        } else if !affordable && !greyed_out {
+            commands
+                .entity(entity)
+                .remove::<MeshMaterial2d<ColorMaterial>>()
+                .insert((
+                    Unaffordable,
+                    MeshMaterial2d(shop_materials.unaffordable.clone()),
+                ));
+        }
+    }
+}
+
 #[derive(Event)]
 struct InvalidateShopLayout;
 fn shop_layout(
@@ -186,9 +461,9 @@ fn shop_layout(
     mut shop_items: Query<(&mut Transform, &ShopOrder)>,
 ) {
     let num = shop_items.iter().count() as f32;
-    let width = (TILE_SIZE * 1.5) * num;
+    let width = (TILE_SIZE * 1.4) * num;
     for (mut transform, index) in shop_items.iter_mut() {
-        transform.translation.x = (-width / 2.0 + TILE_SIZE * (index.0 as f32 + 0.5) * 1.5).round(); // rounded to help with correct dithering
+        transform.translation.x = (-width / 2.0 + TILE_SIZE * (index.0 as f32 + 0.5) * 1.4).round(); // rounded to help with correct dithering
         transform.translation.y = 0.0;
     }
 }
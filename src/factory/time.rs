@@ -11,7 +11,7 @@ pub(super) fn plugin(app: &mut App) {
             Duration::from_secs(1),
             TimerMode::Repeating,
         )))
-        .add_systems(Update, time_consume.in_set(Sets::PostUpdate));
+        .add_systems(FixedUpdate, time_consume.in_set(Sets::PostUpdate));
 }
 
 #[derive(Resource)]
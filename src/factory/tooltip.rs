@@ -2,6 +2,8 @@ use bevy::prelude::*;
 
 use crate::{scheduling::Sets, SCREEN_SIZE};
 
+use super::shop::ShopItem;
+
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(Startup, tooltip_spawn.in_set(Sets::Spawn))
         .add_systems(Update, tooltip_observers.in_set(Sets::Input));
@@ -77,7 +79,7 @@ fn tooltip_observers(
 fn tooltip_update(
     trigger: Trigger<Pointer<Move>>,
     mut commands: Commands,
-    tooltips: Query<&Tooltip>,
+    tooltips: Query<(&Tooltip, Option<&ShopItem>)>,
     active_tooltip: Res<ActiveTooltip>,
     buttons: Res<ButtonInput<MouseButton>>,
 ) {
@@ -88,8 +90,15 @@ fn tooltip_update(
         return;
     }
     let target = trigger.target();
-    if let Ok(tooltip) = tooltips.get(target) {
+    if let Ok((tooltip, shop_item)) = tooltips.get(target) {
         let title = tooltip.0.clone();
+        let description = match (&tooltip.1, shop_item) {
+            (Some(description), Some(item)) => {
+                Some(format!("{description}\n{}", item.cost().description()))
+            }
+            (None, Some(item)) => Some(item.cost().description()),
+            (description, None) => description.clone(),
+        };
         // Update the tooltip text and position
         let position = trigger.pointer_location.position;
         commands
@@ -117,9 +126,9 @@ fn tooltip_update(
                 ..default()
             },
         ));
-        if let Some(description) = &tooltip.1 {
+        if let Some(description) = description {
             commands.entity(active_tooltip.description).insert((
-                Text(description.clone()),
+                Text(description),
                 Node {
                     display: Display::Block,
                     ..default()
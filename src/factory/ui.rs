@@ -6,13 +6,13 @@ use crate::{
     SCREEN_SIZE,
 };
 
-use super::machines::{Buffer, Inlet};
+use super::machines::{Buffer, Inlet, TANK_SIZE};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(Startup, ui_spawn.in_set(Sets::Spawn))
         .add_systems(
             Update,
-            (ui_update, ui_hide_time_warning).in_set(Sets::PostUpdate),
+            (ui_update, ui_update_fuel_gauge, ui_hide_time_warning).in_set(Sets::PostUpdate),
         );
 }
 
@@ -22,8 +22,12 @@ pub struct ResourceDisplay(pub ResourceType);
 #[derive(Component, Clone)]
 pub struct OutOfTimeThing;
 
+/// Bar that shrinks as the ship burns through its fuel
+#[derive(Component, Clone)]
+pub struct FuelGauge;
+
 fn ui_spawn(mut commands: Commands) {
-    const DISPLAY_WIDTH: f32 = 120.0;
+    const DISPLAY_WIDTH: f32 = 95.0;
     commands.spawn((
         Name::new("Resource UI"),
         Node {
@@ -91,6 +95,52 @@ fn ui_spawn(mut commands: Commands) {
                     ..default()
                 }
             ),
+            (
+                Name::new("Energy Display"),
+                ResourceDisplay(ResourceType::Energy),
+                Text::new("Energy: 0"),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Node {
+                    width: Val::Px(DISPLAY_WIDTH),
+                    ..default()
+                }
+            ),
+        ],
+    ));
+    commands.spawn((
+        Name::new("Fuel Gauge Container"),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            top: Val::Px(10.0),
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![
+            (Name::new("Fuel Gauge Label"), Text::new("Fuel")),
+            (
+                Name::new("Fuel Gauge Frame"),
+                Node {
+                    width: Val::Px(100.0),
+                    height: Val::Px(12.0),
+                    border: UiRect::all(Val::Px(1.0)),
+                    ..default()
+                },
+                BorderColor(Color::WHITE),
+                children![(
+                    Name::new("Fuel Gauge"),
+                    FuelGauge,
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::WHITE),
+                )],
+            ),
         ],
     ));
     commands.spawn((
@@ -154,3 +204,13 @@ fn ui_update(
         )));
     }
 }
+
+fn ui_update_fuel_gauge(resources: Res<Resources>, mut gauges: Query<&mut Node, With<FuelGauge>>) {
+    if !resources.is_changed() {
+        return;
+    }
+    let fill = (resources.get(ResourceType::Fuel) / TANK_SIZE).clamp(0.0, 1.0);
+    for mut node in gauges.iter_mut() {
+        node.width = Val::Percent(fill * 100.0);
+    }
+}
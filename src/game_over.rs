@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::{camera::UICamera, resources::Resources, scheduling::Sets};
+use crate::{camera::UICamera, resources::Resources, rng::GameRng, scheduling::Sets};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(Update, restart_game.in_set(Sets::PreUpdate))
@@ -17,6 +17,7 @@ fn game_over(
     _trigger: Trigger<GameOver>,
     mut commands: Commands,
     ui_camera: Single<Entity, With<UICamera>>,
+    rng: Res<GameRng>,
 ) {
     commands.spawn((
         Name::new("Gameover Screen"),
@@ -41,7 +42,10 @@ fn game_over(
                 justify: JustifyText::Center,
                 ..default()
             },
-            Text("You died!\nPress R to restart.".to_string()),
+            Text(format!(
+                "You died!\nPress R to restart.\nSeed: {}",
+                rng.seed()
+            )),
         )],
     ));
 }
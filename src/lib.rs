@@ -1,3 +1,5 @@
+#![cfg_attr(test, feature(test))]
+
 use bevy::{asset::AssetMetaCheck, prelude::*, window::EnabledButtons};
 
 #[cfg(feature = "dev")]
@@ -9,12 +11,17 @@ mod game_over;
 mod layers;
 mod materials;
 mod mesh;
+mod replay;
 mod resources;
+mod rng;
+mod ron_asset;
 mod scheduling;
 mod sounds;
 mod space;
 mod z_order;
 
+pub use factory::headless;
+
 pub const SCREEN_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
 
 pub fn plugin(app: &mut App) {
@@ -54,7 +61,9 @@ pub fn plugin(app: &mut App) {
             factory::plugin,
             game_over::plugin,
             materials::plugin,
+            replay::plugin,
             resources::plugin,
+            rng::plugin,
             scheduling::plugin,
             sounds::plugin,
             space::plugin,
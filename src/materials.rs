@@ -53,6 +53,8 @@ impl From<Dither> for DitherMaterial {
 pub struct RockyDither {
     pub fill: f32,
     pub scale: f32,
+    /// Shifts the noise so neighbouring rocks don't look the same
+    pub offset: Vec2,
 }
 impl From<RockyDither> for DitherMaterial {
     fn from(settings: RockyDither) -> Self {
@@ -61,7 +63,7 @@ impl From<RockyDither> for DitherMaterial {
                 fill: settings.fill,
                 scale: settings.scale,
                 flags: DitherFlags::FBM.bits(),
-                offset: vec2(rand::random(), rand::random()),
+                offset: settings.offset,
                 ..default()
             },
         }
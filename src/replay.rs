@@ -0,0 +1,489 @@
+//! Records keyboard, mouse and cursor input every frame and plays it back later. Recording
+//! runs in real time and keeps how long every frame took, playing back steps the clock by
+//! the same amounts with the same seed, so it reproduces the whole run.
+//!
+//! Set `SPACETIME_RECORD=<file>` to record a session, the replay is written when the game
+//! ends or closes. Set `SPACETIME_REPLAY=<file>` to play one back.
+
+use std::time::Duration;
+
+use bevy::{
+    input::{
+        mouse::{MouseScrollUnit, MouseWheel},
+        InputSystem,
+    },
+    picking::{
+        input::PointerInputPlugin,
+        pointer::{
+            Location, PointerAction, PointerButton, PointerId, PointerInput, PointerLocation,
+        },
+        PickSet,
+    },
+    prelude::*,
+    render::camera::RenderTarget,
+    time::TimeUpdateStrategy,
+    window::{PrimaryWindow, WindowRef},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{game_over::GameOver, rng::GameRng, scheduling::Sets};
+
+pub(super) fn plugin(app: &mut App) {
+    let recording = std::env::var(RECORD_VAR).ok();
+    let playing = std::env::var(REPLAY_VAR).ok();
+    if recording.is_none() && playing.is_none() {
+        return;
+    }
+    app.add_systems(Startup, replay_setup.in_set(Sets::Init))
+        .add_systems(
+            PreUpdate,
+            (replay_record, replay_play)
+                .after(InputSystem)
+                .before(PickSet::ProcessInput),
+        )
+        .add_systems(Last, replay_save_on_exit)
+        .add_observer(replay_save_on_game_over);
+    if let Some(path) = playing {
+        app.insert_resource(ReplayPath(path));
+    } else if let Some(path) = recording {
+        app.insert_resource(ReplayPath(path))
+            .insert_resource(Recorder::default());
+    }
+}
+
+const RECORD_VAR: &str = "SPACETIME_RECORD";
+const REPLAY_VAR: &str = "SPACETIME_REPLAY";
+
+/// A recorded run. Only frames where something changed are stored.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct Replay {
+    pub seed: u64,
+    /// How long every frame of the run took in nanoseconds, the fixed timestep has to
+    /// catch up by the same amount when playing back
+    pub deltas: Vec<u32>,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    /// Total number of frames the run lasted
+    pub fn length(&self) -> u32 {
+        self.deltas.len() as u32
+    }
+
+    fn delta(&self, frame: u32) -> Option<Duration> {
+        self.deltas
+            .get(frame as usize)
+            .map(|&nanos| Duration::from_nanos(nanos.into()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct ReplayFrame {
+    pub frame: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys_down: Vec<KeyCode>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys_up: Vec<KeyCode>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buttons_down: Vec<MouseButton>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buttons_up: Vec<MouseButton>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<Cursor>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub scroll: f32,
+}
+
+fn is_zero(value: &f32) -> bool {
+    *value == 0.0
+}
+
+impl ReplayFrame {
+    fn is_empty(&self) -> bool {
+        *self
+            == Self {
+                frame: self.frame,
+                ..default()
+            }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Cursor {
+    /// Moved to this position in the window, in logical pixels
+    Moved(Vec2),
+    /// Left the window
+    Left,
+}
+
+#[derive(Resource, Clone)]
+struct ReplayPath(String);
+
+#[derive(Resource, Default)]
+struct Recorder {
+    replay: Replay,
+    cursor: Option<Vec2>,
+}
+
+/// Feeds a replay into the input resources, taking over from the real devices.
+#[derive(Resource)]
+struct Player {
+    replay: Replay,
+    frame: u32,
+    next: usize,
+    keys: ButtonInput<KeyCode>,
+    buttons: ButtonInput<MouseButton>,
+    cursor: Option<Vec2>,
+}
+
+fn replay_setup(
+    mut commands: Commands,
+    path: Res<ReplayPath>,
+    recorder: Option<ResMut<Recorder>>,
+    mut rng: ResMut<GameRng>,
+    mut pointer_input: ResMut<PointerInputPlugin>,
+) {
+    if let Some(mut recorder) = recorder {
+        // restarts have to roll the same numbers when played back
+        *rng = GameRng::fixed(rng.seed());
+        recorder.replay.seed = rng.seed();
+        info!("Recording replay to {}", path.0);
+        return;
+    }
+    let serialized = match std::fs::read_to_string(&path.0) {
+        Ok(serialized) => serialized,
+        Err(error) => {
+            warn!("Failed to read replay from {}: {error}", path.0);
+            return;
+        }
+    };
+    let replay = match ron::from_str::<Replay>(&serialized) {
+        Ok(replay) => replay,
+        Err(error) => {
+            warn!("Failed to parse replay from {}: {error}", path.0);
+            return;
+        }
+    };
+    info!(
+        "Playing replay from {}, {} frames with seed {}",
+        path.0,
+        replay.length(),
+        replay.seed
+    );
+    *rng = GameRng::fixed(replay.seed);
+    if let Some(delta) = replay.delta(0) {
+        commands.insert_resource(TimeUpdateStrategy::ManualDuration(delta));
+    }
+    // picking only follows the replayed pointer
+    pointer_input.is_mouse_enabled = false;
+    commands.insert_resource(Player {
+        replay,
+        frame: 0,
+        next: 0,
+        keys: default(),
+        buttons: default(),
+        cursor: None,
+    });
+}
+
+fn replay_record(
+    recorder: Option<ResMut<Recorder>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut scroll: EventReader<MouseWheel>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    time: Res<Time<Real>>,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+    let cursor = window.cursor_position();
+    let frame = ReplayFrame {
+        frame: recorder.replay.length(),
+        keys_down: keys.get_just_pressed().copied().collect(),
+        keys_up: keys.get_just_released().copied().collect(),
+        buttons_down: buttons.get_just_pressed().copied().collect(),
+        buttons_up: buttons.get_just_released().copied().collect(),
+        cursor: (cursor != recorder.cursor).then_some(cursor.map_or(Cursor::Left, Cursor::Moved)),
+        scroll: scroll.read().map(|event| event.y).sum(),
+    };
+    recorder.cursor = cursor;
+    let delta = time.delta().as_nanos().min(u32::MAX.into()) as u32;
+    recorder.replay.deltas.push(delta);
+    if !frame.is_empty() {
+        recorder.replay.frames.push(frame);
+    }
+}
+
+fn replay_play(
+    mut commands: Commands,
+    player: Option<ResMut<Player>>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut buttons: ResMut<ButtonInput<MouseButton>>,
+    mut pointer_events: EventWriter<PointerInput>,
+    mut scroll_events: EventWriter<MouseWheel>,
+    mut pointer_input: ResMut<PointerInputPlugin>,
+    mut pointers: Query<(&PointerId, &mut PointerLocation)>,
+    window: Single<Entity, With<PrimaryWindow>>,
+) {
+    let Some(mut player) = player else {
+        return;
+    };
+    let player = &mut *player;
+    let window_entity = *window;
+    if player.frame >= player.replay.length() {
+        info!("Replay finished");
+        pointer_input.is_mouse_enabled = true;
+        commands.insert_resource(TimeUpdateStrategy::Automatic);
+        commands.remove_resource::<Player>();
+        return;
+    }
+
+    player.keys.clear();
+    player.buttons.clear();
+    let Some(target) =
+        RenderTarget::Window(WindowRef::Entity(window_entity)).normalize(Some(window_entity))
+    else {
+        return;
+    };
+    let location = |position| Location {
+        target: target.clone(),
+        position,
+    };
+    while let Some(frame) = player
+        .replay
+        .frames
+        .get(player.next)
+        .filter(|frame| frame.frame == player.frame)
+    {
+        player.next += 1;
+        apply_buttons(&mut player.keys, &frame.keys_down, &frame.keys_up);
+        apply_buttons(&mut player.buttons, &frame.buttons_down, &frame.buttons_up);
+        match frame.cursor {
+            Some(Cursor::Moved(position)) => {
+                let delta = position - player.cursor.unwrap_or(position);
+                player.cursor = Some(position);
+                pointer_events.write(PointerInput::new(
+                    PointerId::Mouse,
+                    location(position),
+                    PointerAction::Move { delta },
+                ));
+            }
+            Some(Cursor::Left) => {
+                player.cursor = None;
+                for (_, mut pointer) in pointers.iter_mut().filter(|(id, _)| id.is_mouse()) {
+                    pointer.location = None;
+                }
+            }
+            None => {}
+        }
+        let position = player.cursor.unwrap_or_default();
+        for button in frame.buttons_up.iter() {
+            if let Some(button) = pointer_button(*button) {
+                pointer_events.write(PointerInput::new(
+                    PointerId::Mouse,
+                    location(position),
+                    PointerAction::Release(button),
+                ));
+            }
+        }
+        for button in frame.buttons_down.iter() {
+            if let Some(button) = pointer_button(*button) {
+                pointer_events.write(PointerInput::new(
+                    PointerId::Mouse,
+                    location(position),
+                    PointerAction::Press(button),
+                ));
+            }
+        }
+        if frame.scroll != 0.0 {
+            scroll_events.write(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: 0.0,
+                y: frame.scroll,
+                window: window_entity,
+            });
+        }
+    }
+    player.frame += 1;
+    if let Some(delta) = player.replay.delta(player.frame) {
+        commands.insert_resource(TimeUpdateStrategy::ManualDuration(delta));
+    }
+
+    *keys = player.keys.clone();
+    *buttons = player.buttons.clone();
+}
+
+/// Presses and releases buttons the same way a frame of real input would. A button that is
+/// both pressed and released was tapped if it wasn't held before, and pressed again if it was.
+fn apply_buttons<T: Copy + Eq + std::hash::Hash + Send + Sync + 'static>(
+    input: &mut ButtonInput<T>,
+    down: &[T],
+    up: &[T],
+) {
+    let held = up
+        .iter()
+        .copied()
+        .filter(|button| input.pressed(*button))
+        .collect::<Vec<_>>();
+    for button in held.iter() {
+        input.release(*button);
+    }
+    for button in down.iter() {
+        input.press(*button);
+    }
+    for button in up.iter() {
+        if !held.contains(button) {
+            input.release(*button);
+        }
+    }
+}
+
+fn pointer_button(button: MouseButton) -> Option<PointerButton> {
+    match button {
+        MouseButton::Left => Some(PointerButton::Primary),
+        MouseButton::Right => Some(PointerButton::Secondary),
+        MouseButton::Middle => Some(PointerButton::Middle),
+        _ => None,
+    }
+}
+
+fn save_replay(recorder: &Recorder, path: &ReplayPath) {
+    let serialized = match ron::to_string(&recorder.replay) {
+        Ok(serialized) => serialized,
+        Err(error) => {
+            warn!("Failed to serialize replay: {error}");
+            return;
+        }
+    };
+    match std::fs::write(&path.0, serialized) {
+        Ok(()) => info!(
+            "Saved replay of {} frames to {}",
+            recorder.replay.length(),
+            path.0
+        ),
+        Err(error) => warn!("Failed to write replay to {}: {error}", path.0),
+    }
+}
+
+fn replay_save_on_exit(
+    mut exits: EventReader<AppExit>,
+    recorder: Option<Res<Recorder>>,
+    path: Res<ReplayPath>,
+) {
+    if exits.read().count() == 0 {
+        return;
+    }
+    if let Some(recorder) = recorder {
+        save_replay(&recorder, &path);
+    }
+}
+
+fn replay_save_on_game_over(
+    _trigger: Trigger<GameOver>,
+    recorder: Option<Res<Recorder>>,
+    path: Res<ReplayPath>,
+) {
+    if let Some(recorder) = recorder {
+        save_replay(&recorder, &path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState, InputPlugin,
+    };
+    use rand::Rng;
+
+    use super::*;
+    use crate::rng::RngStream;
+
+    /// Stands in for the game, it moves while D is held and rolls a number every step
+    #[derive(Resource, Default, Debug, Clone, PartialEq)]
+    struct Simulation {
+        steps: u32,
+        position: f32,
+        rolls: Vec<u32>,
+    }
+
+    fn simulate(
+        keys: Res<ButtonInput<KeyCode>>,
+        time: Res<Time>,
+        mut simulation: ResMut<Simulation>,
+        mut rng: ResMut<GameRng>,
+    ) {
+        simulation.steps += 1;
+        if keys.pressed(KeyCode::KeyD) {
+            simulation.position += time.delta_secs();
+            let roll = rng.stream(RngStream::Asteroids).gen();
+            simulation.rolls.push(roll);
+        }
+    }
+
+    fn app(path: &str) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin))
+            .add_event::<PointerInput>()
+            .init_resource::<PointerInputPlugin>()
+            .init_resource::<Simulation>()
+            .insert_resource(GameRng::new(rand::random()))
+            .insert_resource(ReplayPath(path.to_string()))
+            .add_systems(Startup, replay_setup)
+            .add_systems(PreUpdate, (replay_record, replay_play).after(InputSystem))
+            .add_systems(FixedUpdate, simulate);
+        app.world_mut().spawn((Window::default(), PrimaryWindow));
+        app
+    }
+
+    fn press(app: &mut App, key_code: KeyCode, state: ButtonState) {
+        let window = app
+            .world_mut()
+            .query_filtered::<Entity, With<PrimaryWindow>>()
+            .single(app.world())
+            .unwrap();
+        app.world_mut().send_event(KeyboardInput {
+            key_code,
+            logical_key: Key::Character("d".into()),
+            state,
+            text: None,
+            repeat: false,
+            window,
+        });
+    }
+
+    #[test]
+    fn playing_a_recording_reproduces_the_run() {
+        let path = std::env::temp_dir()
+            .join(format!("spacetime-replay-{}.ron", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+
+        let mut recording = app(&path);
+        recording.init_resource::<Recorder>();
+        for frame in 0..300u64 {
+            // uneven frames, so some run several fixed steps and some none
+            let delta = Duration::from_micros(5_000 + frame * 7_919 % 30_000);
+            recording.insert_resource(TimeUpdateStrategy::ManualDuration(delta));
+            match frame {
+                20 | 150 => press(&mut recording, KeyCode::KeyD, ButtonState::Pressed),
+                90 | 240 => press(&mut recording, KeyCode::KeyD, ButtonState::Released),
+                _ => {}
+            }
+            recording.update();
+        }
+        save_replay(
+            recording.world().resource::<Recorder>(),
+            recording.world().resource::<ReplayPath>(),
+        );
+        let recorded = recording.world().resource::<Simulation>().clone();
+        assert!(!recorded.rolls.is_empty());
+
+        let mut playing = app(&path);
+        for _ in 0..300 {
+            playing.update();
+        }
+        assert_eq!(*playing.world().resource::<Simulation>(), recorded);
+    }
+}
@@ -1,10 +1,11 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 pub(super) fn plugin(app: &mut App) {
     app.init_resource::<Resources>();
 }
 
-#[derive(Reflect, Component, Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Reflect, Component, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ResourceType {
     Health,
     Mineral,
@@ -12,6 +13,8 @@ pub enum ResourceType {
     Time,
     Ammo,
     Rockets,
+    Energy,
+    Fuel,
 }
 
 impl ResourceType {
@@ -23,29 +26,40 @@ impl ResourceType {
             Self::Time => "Time".to_string(),
             Self::Ammo => "Ammo".to_string(),
             Self::Rockets => "Rockets".to_string(),
+            Self::Energy => "Energy".to_string(),
+            Self::Fuel => "Fuel".to_string(),
         }
     }
 }
 
+/// Hull health of a fully repaired ship
+pub const MAX_HEALTH: f32 = 100.0;
+
 #[derive(Resource, Debug)]
 pub struct Resources {
+    /// Copy of the ship's hull health, change it through [`RepairShip`] instead
     pub health: f32,
     pub minerals: f32,
     pub gas: f32,
     pub time: f32,
     pub ammo: f32,
     pub rockets: f32,
+    pub energy: f32,
+    /// Burnt by the ship's thrusters, for moving and turning
+    pub fuel: f32,
 }
 
 impl Default for Resources {
     fn default() -> Self {
         Self {
-            health: 100.0,
+            health: MAX_HEALTH,
             minerals: 10.0,
             gas: 0.0,
             time: 30.0,
             ammo: 20.0,
             rockets: 0.0,
+            energy: 0.0,
+            fuel: 10.0,
         }
     }
 }
@@ -59,6 +73,8 @@ impl Resources {
             ResourceType::Time => self.time,
             ResourceType::Ammo => self.ammo,
             ResourceType::Rockets => self.rockets,
+            ResourceType::Energy => self.energy,
+            ResourceType::Fuel => self.fuel,
         }
     }
 
@@ -70,6 +86,16 @@ impl Resources {
             ResourceType::Time => self.time += amount,
             ResourceType::Ammo => self.ammo += amount,
             ResourceType::Rockets => self.rockets += amount,
+            ResourceType::Energy => self.energy += amount,
+            ResourceType::Fuel => self.fuel += amount,
         }
     }
 }
+
+/// Crafted in the factory, upgrades one of the ship's weapons
+#[derive(Event, Clone, Copy, Debug)]
+pub struct UpgradeWeapon;
+
+/// Crafted in the factory, repairs the ship's hull by this much
+#[derive(Event, Clone, Copy, Debug)]
+pub struct RepairShip(pub f32);
@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::game_over::RestartGame;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(GameRng::from_env())
+        .add_observer(rng_restart);
+}
+
+/// Environment variable that fixes the seed, for bug repros and challenge runs
+const SEED_VAR: &str = "SPACETIME_SEED";
+
+/// Independent sources of randomness, one per subsystem.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RngStream {
+    Asteroids,
+    Gas,
+    Enemies,
+    Pickups,
+    Particles,
+    Weapons,
+    Visuals,
+}
+
+impl RngStream {
+    const ALL: [RngStream; 7] = [
+        RngStream::Asteroids,
+        RngStream::Gas,
+        RngStream::Enemies,
+        RngStream::Pickups,
+        RngStream::Particles,
+        RngStream::Weapons,
+        RngStream::Visuals,
+    ];
+}
+
+/// Source of all gameplay randomness. Every subsystem draws from its own stream derived
+/// from the seed, so rolling more numbers in one doesn't change what the others roll.
+#[derive(Resource, Debug)]
+pub struct GameRng {
+    seed: u64,
+    /// The seed was chosen by the player and is kept when the game restarts
+    fixed: bool,
+    streams: Vec<StdRng>,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        let streams = RngStream::ALL
+            .iter()
+            .map(|&stream| {
+                let index = stream as u64;
+                StdRng::seed_from_u64(seed ^ (index + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15))
+            })
+            .collect();
+        Self {
+            seed,
+            fixed: false,
+            streams,
+        }
+    }
+
+    /// Same as [`GameRng::new`], but keeps the seed when the game restarts.
+    pub fn fixed(seed: u64) -> Self {
+        Self {
+            fixed: true,
+            ..Self::new(seed)
+        }
+    }
+
+    /// Uses the seed in `SPACETIME_SEED` if it is set, a random one otherwise.
+    pub fn from_env() -> Self {
+        let fixed = std::env::var(SEED_VAR)
+            .ok()
+            .and_then(|seed| seed.trim().parse().ok());
+        let rng = match fixed {
+            Some(seed) => Self::fixed(seed),
+            None => Self::new(rand::random()),
+        };
+        info!("Game seed: {}", rng.seed);
+        rng
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut StdRng {
+        &mut self.streams[stream as usize]
+    }
+}
+
+/// Replays the same seed if it was fixed, starts a fresh run otherwise.
+fn rng_restart(_trigger: Trigger<RestartGame>, mut rng: ResMut<GameRng>) {
+    let seed = if rng.fixed { rng.seed } else { rand::random() };
+    *rng = GameRng {
+        fixed: rng.fixed,
+        ..GameRng::new(seed)
+    };
+    info!("Game seed: {}", rng.seed);
+}
@@ -0,0 +1,49 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use serde::de::DeserializeOwned;
+
+/// Asset that is deserialized straight from a RON file.
+pub trait RonAsset: Asset + DeserializeOwned {
+    /// File extensions the loader claims, e.g. `recipe.ron`
+    const EXTENSIONS: &'static [&'static str];
+
+    /// Rejects values that parse but can't work, failing the load with the message.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+pub struct RonLoader<T>(PhantomData<T>);
+
+impl<T> Default for RonLoader<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: RonAsset> AssetLoader for RonLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<T, Self::Error> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+        let asset = ron::de::from_bytes::<T>(&bytes)?;
+        asset.validate()?;
+        Ok(asset)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        T::EXTENSIONS
+    }
+}
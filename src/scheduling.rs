@@ -1,9 +1,15 @@
-use bevy::prelude::*;
+use std::time::Duration;
+
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
 
 pub(super) fn plugin(app: &mut App) {
-    app.configure_sets(Startup, (Sets::Init, Sets::Spawn).chain())
-        .configure_sets(
-            Update,
+    app.insert_resource(Time::<Fixed>::from_duration(TIMESTEP))
+        .configure_sets(Startup, (Sets::Init, Sets::Spawn).chain());
+    // The simulation runs in `FixedUpdate`, everything that only reacts to input or draws
+    // runs in `Update`. Both use the same sets.
+    for schedule in [FixedUpdate.intern(), Update.intern()] {
+        app.configure_sets(
+            schedule,
             (
                 Sets::PreUpdate,
                 Sets::Input,
@@ -15,8 +21,12 @@ pub(super) fn plugin(app: &mut App) {
             )
                 .chain(),
         );
+    }
 }
 
+/// Length of a `FixedUpdate` step.
+pub const TIMESTEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
 #[derive(SystemSet, Hash, Debug, Clone, PartialEq, Eq)]
 pub enum Sets {
     /// Used for initializing resources and essential entities
@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+
+use crate::{
+    materials::{DitherMaterial, GassyDither, MetalDither, RockyDither},
+    space::{
+        drops::{DropTable, DropTables},
+        health::Resistances,
+    },
+};
+
+/// What an asteroid is made of, which decides how it looks, how tough it is and what it
+/// leaves behind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AsteroidKind {
+    /// Common rock full of minerals
+    #[default]
+    Iron,
+    /// Frozen gas, brittle
+    Ice,
+    /// Rare and tough, holds time
+    Crystal,
+    /// Detonates when broken, hurting everything close by
+    Explosive,
+}
+
+impl AsteroidKind {
+    const ALL: [AsteroidKind; 4] = [
+        AsteroidKind::Iron,
+        AsteroidKind::Ice,
+        AsteroidKind::Crystal,
+        AsteroidKind::Explosive,
+    ];
+
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let weights = WeightedIndex::new(Self::ALL.map(Self::spawn_weight)).unwrap();
+        Self::ALL[weights.sample(rng)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AsteroidKind::Iron => "Iron Asteroid",
+            AsteroidKind::Ice => "Ice Asteroid",
+            AsteroidKind::Crystal => "Crystal Asteroid",
+            AsteroidKind::Explosive => "Explosive Asteroid",
+        }
+    }
+
+    /// How often this kind spawns compared to the others
+    pub fn spawn_weight(self) -> u32 {
+        match self {
+            AsteroidKind::Iron => 6,
+            AsteroidKind::Ice => 3,
+            AsteroidKind::Crystal => 1,
+            AsteroidKind::Explosive => 2,
+        }
+    }
+
+    /// Multiplies the health an asteroid of this size would have
+    pub fn health_scale(self) -> f32 {
+        match self {
+            AsteroidKind::Iron => 1.0,
+            AsteroidKind::Ice => 0.6,
+            AsteroidKind::Crystal => 1.5,
+            AsteroidKind::Explosive => 0.5,
+        }
+    }
+
+    /// Crystal shrugs off bullets but cuts easily with the mining laser
+    pub fn resistances(self) -> Resistances {
+        match self {
+            AsteroidKind::Crystal => Resistances {
+                kinetic: 0.5,
+                ..default()
+            },
+            AsteroidKind::Iron => Resistances {
+                kinetic: 0.2,
+                ..default()
+            },
+            _ => Resistances::default(),
+        }
+    }
+
+    /// Fill of the material at full health
+    pub fn fill(self) -> f32 {
+        match self {
+            AsteroidKind::Iron => 0.8,
+            AsteroidKind::Ice => 0.4,
+            AsteroidKind::Crystal => 0.9,
+            AsteroidKind::Explosive => 0.35,
+        }
+    }
+
+    pub fn material(self, rng: &mut impl Rng) -> DitherMaterial {
+        let fill = self.fill();
+        match self {
+            AsteroidKind::Iron => RockyDither {
+                fill,
+                scale: 50.0,
+                offset: vec2(rng.gen(), rng.gen()),
+            }
+            .into(),
+            AsteroidKind::Ice => GassyDither { fill, scale: 25.0 }.into(),
+            AsteroidKind::Crystal => MetalDither { fill, scale: 8.0 }.into(),
+            AsteroidKind::Explosive => MetalDither { fill, scale: 30.0 }.into(),
+        }
+    }
+
+    /// Rolled by the smallest fragments when they break, and by explosive ones when they go off
+    pub fn drop_table(self, tables: &DropTables) -> Handle<DropTable> {
+        match self {
+            AsteroidKind::Iron => tables.iron.clone(),
+            AsteroidKind::Ice => tables.ice.clone(),
+            AsteroidKind::Crystal => tables.crystal.clone(),
+            AsteroidKind::Explosive => tables.explosive.clone(),
+        }
+    }
+}
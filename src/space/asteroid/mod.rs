@@ -0,0 +1,341 @@
+use std::time::Duration;
+
+use bevy::{ecs::error, prelude::*};
+use rand::{distributions::uniform::SampleRange, Rng};
+
+use crate::{
+    layers::SpaceLayer,
+    materials::{DitherMaterial, SOLID_WHITE},
+    mesh::MeshLyonExtensions,
+    rng::{GameRng, RngStream},
+    scheduling::Sets,
+    sounds::Sounds,
+    z_order::ZOrder,
+};
+
+use super::{
+    bounds::ScreenBounds,
+    collision::{Collider, CollisionLayers, Collisions, Layer},
+    drops::{DropLoot, DropTables, Drops},
+    health::{damage, Damage, Death, Health},
+    particles::EmitParticles,
+    physics::{Rotation, Spin, Velocity},
+    rigid_body::{Mass, Restitution},
+    ship::Ship,
+};
+
+mod kind;
+
+pub use kind::AsteroidKind;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, asteroid_collide.in_set(Sets::Update))
+        .add_systems(
+            Update,
+            (
+                asteroid_spawn_timer.in_set(Sets::PreUpdate),
+                asteroid_display_health.in_set(Sets::PostUpdate),
+            ),
+        )
+        .add_observer(asteroid_break)
+        .add_observer(asteroid_explode);
+}
+
+pub fn generate_asteroid_shape(
+    rng: &mut impl Rng,
+    vertex_range: impl SampleRange<i32>,
+    radius_range: impl SampleRange<f32> + Clone,
+) -> Vec<Vec2> {
+    let mut vertices = vec![];
+    let corner_count = rng.gen_range(vertex_range);
+    for i in 0..corner_count {
+        let radius = rng.gen_range(radius_range.clone());
+        let angle = (i as f32 / corner_count as f32) * std::f32::consts::PI * 2.0;
+        let x = angle.cos() * radius;
+        let y = angle.sin() * radius;
+        vertices.push(vec2(x, y));
+    }
+    vertices
+}
+
+pub fn polygon_area(vertices: &[Vec2]) -> f32 {
+    vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum::<f32>()
+        .abs()
+        * 0.5
+}
+
+/// Mass of an asteroid with this outline, the ship weighs 1
+pub fn asteroid_mass(vertices: &[Vec2]) -> f32 {
+    polygon_area(vertices) / 1500.0
+}
+
+/// Asteroids at least this big break into smaller ones instead of dropping their loot
+const FRACTURE_AREA: f32 = 2000.0;
+/// An average fresh asteroid has about 100 health
+const HEALTH_PER_AREA: f32 = 0.025;
+
+#[derive(Component, Clone)]
+pub struct Asteroid {
+    pub kind: AsteroidKind,
+    /// Outline around the asteroid's origin, fragments are cut out of it
+    pub vertices: Vec<Vec2>,
+}
+
+/// Everything but the movement of an asteroid with this outline
+pub fn asteroid(
+    kind: AsteroidKind,
+    vertices: Vec<Vec2>,
+    transform: Transform,
+    rng: &mut impl Rng,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<DitherMaterial>>,
+) -> impl Bundle {
+    (
+        Name::new(kind.name()),
+        SpaceLayer,
+        Mesh2d(meshes.add(Mesh::stroke_polygon(&vertices, 5.0))),
+        MeshMaterial2d(SOLID_WHITE),
+        transform,
+        ZOrder::ASTEROID,
+        Collider::from_vertices(&vertices),
+        CollisionLayers::new(
+            Layer::ASTEROID,
+            Layer::SHIP | Layer::SHIP_PROJECTILE | Layer::ENEMY | Layer::ASTEROID,
+        ),
+        Mass(asteroid_mass(&vertices)),
+        Restitution(0.8),
+        Health::new(polygon_area(&vertices) * HEALTH_PER_AREA * kind.health_scale()),
+        kind.resistances(),
+        children![(
+            SpaceLayer,
+            Mesh2d(meshes.add(Mesh::fill_polygon(&vertices))),
+            MeshMaterial2d(materials.add(kind.material(rng))),
+            Transform::from_xyz(0.0, 0.0, 1.0),
+        )],
+        Asteroid { kind, vertices },
+    )
+}
+
+/// Cuts an outline around the origin into `pieces` wedges that meet in the middle, each
+/// keeping a run of the original vertices. Returns the centre of every wedge with its
+/// outline around that centre.
+fn fracture(vertices: &[Vec2], pieces: usize, rng: &mut impl Rng) -> Vec<(Vec2, Vec<Vec2>)> {
+    let count = vertices.len();
+    let start = rng.gen_range(0..count);
+    (0..pieces)
+        .map(|piece| {
+            let from = start + piece * count / pieces;
+            let to = start + (piece + 1) * count / pieces;
+            let mut outline = vec![Vec2::ZERO];
+            outline.extend((from..=to).map(|i| vertices[i % count]));
+            let centre = outline.iter().sum::<Vec2>() / outline.len() as f32;
+            (centre, outline.iter().map(|v| *v - centre).collect())
+        })
+        .collect()
+}
+
+fn asteroid_spawn_timer(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut timer: Local<Option<Timer>>,
+    asteroids: Query<&Asteroid>,
+) {
+    let asteroid_count = asteroids.iter().count();
+    if asteroid_count >= 10 {
+        return;
+    }
+    if let Some(timer) = timer.as_mut() {
+        if timer.tick(time.delta()).just_finished() {
+            commands.run_system_cached(asteroid_spawn);
+        }
+    } else {
+        timer.replace(Timer::new(
+            Duration::from_secs_f32(5.0),
+            TimerMode::Repeating,
+        ));
+        commands.run_system_cached(asteroid_spawn);
+    }
+}
+
+fn asteroid_spawn(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<DitherMaterial>>,
+    bounds: ScreenBounds,
+    mut rng: ResMut<GameRng>,
+    drop_tables: Res<DropTables>,
+) {
+    let rng = rng.stream(RngStream::Asteroids);
+    let kind = AsteroidKind::random(rng);
+    let vertices = generate_asteroid_shape(rng, 9..=15, 25.0..=50.0);
+    let pos = bounds.random_outside(rng) * 1.2;
+    let target = bounds.random_inside(rng) * 0.8;
+    commands.spawn((
+        asteroid(
+            kind,
+            vertices,
+            Transform::from_xyz(pos.x, pos.y, 0.0),
+            rng,
+            &mut meshes,
+            &mut materials,
+        ),
+        Drops(kind.drop_table(&drop_tables)),
+        Velocity::random_towards(rng, target - pos, 60.0..150.0),
+        Spin::random(rng, 90.0),
+    ));
+}
+
+fn asteroid_collide(
+    mut commands: Commands,
+    mut collisions: Collisions,
+    mut particles: EventWriter<EmitParticles>,
+    asteroids: Query<(), With<Asteroid>>,
+    ship: Single<Entity, With<Ship>>,
+    sounds: Res<Sounds>,
+) {
+    for event in collisions.read(Layer::ASTEROID, Layer::SHIP) {
+        if asteroids.contains(event.entity_a) && event.entity_b == *ship {
+            // Handle collision between asteroid and ship, the bounce is up to the rigid bodies
+            let contact = &event.contact;
+            commands
+                .entity(*ship)
+                .queue_handled(damage(Damage::impact(10.0)), error::ignore);
+            commands.spawn((
+                Name::new("Asteroid Bump Sound"),
+                AudioPlayer::new(sounds.asteroid_bump.clone()),
+                PlaybackSettings::DESPAWN,
+            ));
+            particles.write(EmitParticles {
+                position: contact.point_a,
+                count: 10,
+            });
+            info!("Ship collided with asteroid at {:?}", contact.point_a);
+        }
+    }
+}
+
+fn asteroid_break(
+    trigger: Trigger<Death>,
+    mut commands: Commands,
+    asteroids: Query<(&Transform, &Asteroid, &Velocity, &Rotation, &Spin)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<DitherMaterial>>,
+    sounds: Res<Sounds>,
+    mut rng: ResMut<GameRng>,
+    drop_tables: Res<DropTables>,
+) {
+    let entity = trigger.target();
+    let Ok((transform, broken, velocity, rotation, spin)) = asteroids.get(entity) else {
+        return;
+    };
+    let rng = rng.stream(RngStream::Asteroids);
+    commands.spawn((
+        Name::new("Asteroid Break Sound"),
+        AudioPlayer::new(sounds.asteroid_break.clone()),
+        PlaybackSettings::DESPAWN,
+    ));
+
+    let pieces = rng.gen_range(2..=3);
+    if broken.kind == AsteroidKind::Explosive {
+        commands.trigger(AsteroidExplode {
+            position: transform.translation.truncate(),
+        });
+        commands.trigger_targets(DropLoot, entity);
+    } else if polygon_area(&broken.vertices) >= FRACTURE_AREA && broken.vertices.len() >= pieces * 2
+    {
+        for (centre, vertices) in fracture(&broken.vertices, pieces, rng) {
+            let offset = transform.rotation * centre.extend(0.0);
+            commands.spawn((
+                asteroid(
+                    broken.kind,
+                    vertices,
+                    transform.with_translation(transform.translation + offset),
+                    rng,
+                    &mut meshes,
+                    &mut materials,
+                ),
+                Drops(broken.kind.drop_table(&drop_tables)),
+                Velocity(
+                    velocity.0 + offset.truncate().normalize_or_zero() * rng.gen_range(30.0..80.0),
+                ),
+                Rotation(rotation.0),
+                Spin(spin.0 + rng.gen_range(-45.0..45.0)),
+            ));
+        }
+    } else {
+        // only the smallest pieces are worth mining
+        commands.trigger_targets(DropLoot, entity);
+    }
+    commands.entity(entity).despawn();
+}
+
+#[derive(Event)]
+pub struct AsteroidExplode {
+    pub position: Vec2,
+}
+
+const EXPLOSION_RADIUS: f32 = 200.0;
+const EXPLOSION_DAMAGE: f32 = 40.0;
+const EXPLOSION_PUSH: f32 = 400.0;
+
+/// Hurts and pushes away everything in range, less the further away it is. Can set off
+/// other explosive asteroids.
+fn asteroid_explode(
+    trigger: Trigger<AsteroidExplode>,
+    mut commands: Commands,
+    mut particles: EventWriter<EmitParticles>,
+    targets: Query<(Entity, &Transform), With<Health>>,
+    mut bodies: Query<(&Transform, &mut Velocity, &Mass)>,
+    sounds: Res<Sounds>,
+) {
+    let position = trigger.position;
+    let falloff = |transform: &Transform| {
+        let distance = transform.translation.truncate().distance(position);
+        (1.0 - distance / EXPLOSION_RADIUS).max(0.0)
+    };
+    particles.write(EmitParticles {
+        position,
+        count: 40,
+    });
+    commands.spawn((
+        Name::new("Asteroid Explosion Sound"),
+        AudioPlayer::new(sounds.enemy_die.clone()),
+        PlaybackSettings::DESPAWN,
+    ));
+
+    for (target, transform) in targets.iter() {
+        let amount = falloff(transform);
+        if amount > 0.0 {
+            commands.entity(target).queue_handled(
+                damage(Damage::explosive(EXPLOSION_DAMAGE * amount)),
+                error::ignore,
+            );
+        }
+    }
+    for (transform, mut velocity, mass) in bodies.iter_mut() {
+        let amount = falloff(transform);
+        let direction = (transform.translation.truncate() - position).normalize_or_zero();
+        velocity.0 += direction * EXPLOSION_PUSH * amount / mass.0;
+    }
+}
+
+fn asteroid_display_health(
+    asteroids: Query<(&Asteroid, &Health, &Children), Changed<Health>>,
+    material_handles: Query<&MeshMaterial2d<DitherMaterial>, With<ChildOf>>,
+    mut materials: ResMut<Assets<DitherMaterial>>,
+) {
+    for (asteroid, health, children) in asteroids.iter() {
+        for child in children.iter() {
+            if let Ok(material) = material_handles.get(child) {
+                if let Some(custom_material) = materials.get_mut(&material.0) {
+                    custom_material.settings.fill =
+                        asteroid.kind.fill() * (0.25 + 0.75 * health.fraction());
+                }
+            }
+        }
+    }
+}
@@ -1,18 +1,22 @@
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bitflags::bitflags;
 use parry2d::{
-    na::{Isometry2, Vector2},
-    query,
+    na::{Isometry2, Point2, Vector2},
+    query::{self, ShapeCastOptions},
     shape::{Ball, Compound, SharedShape, TriMesh},
 };
 
 use crate::scheduling::Sets;
 
+use super::physics::Velocity;
+
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(Update, do_collision.in_set(Sets::Collision))
+    app.add_systems(FixedUpdate, do_collision.in_set(Sets::Collision))
         .add_event::<CollisionEvent>();
 }
 
 #[derive(Component, Clone, Debug)]
+#[require(CollisionLayers)]
 pub struct Collider {
     shape: Compound,
     radius: f32,
@@ -38,6 +42,11 @@ impl Collider {
         Self::from_shape(shape)
     }
 
+    /// Radius of the bounding circle around the collider's origin
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
     pub fn from_circle(radius: f32) -> Self {
         let shape = Compound::new(vec![(
             Isometry2::identity(),
@@ -47,6 +56,49 @@ impl Collider {
     }
 }
 
+bitflags! {
+    #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Layer: u32 {
+        const SHIP = 1 << 0;
+        const SHIP_PROJECTILE = 1 << 1;
+        const ENEMY = 1 << 2;
+        const ENEMY_PROJECTILE = 1 << 3;
+        const ASTEROID = 1 << 4;
+        const GAS = 1 << 5;
+        const PICKUP = 1 << 6;
+    }
+}
+
+/// Moves far enough in one step to pass through other colliders. Instead of only checking
+/// where it ends up, its path over the step is swept and the earliest hit along it reported.
+#[derive(Component, Clone, Debug)]
+pub struct FastMover;
+
+/// Which layers a collider is on and which it collides with. Two colliders are only tested
+/// against each other when both masks contain the other's membership. Colliders without
+/// layers are on every layer and collide with everything.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollisionLayers {
+    pub membership: Layer,
+    pub mask: Layer,
+}
+
+impl CollisionLayers {
+    pub const fn new(membership: Layer, mask: Layer) -> Self {
+        Self { membership, mask }
+    }
+
+    pub fn interacts(&self, other: &Self) -> bool {
+        self.mask.intersects(other.membership) && other.mask.intersects(self.membership)
+    }
+}
+
+impl Default for CollisionLayers {
+    fn default() -> Self {
+        Self::new(Layer::all(), Layer::all())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Contact {
     pub point_a: Vec2,
@@ -70,18 +122,10 @@ impl Contact {
         if distance > max_distance {
             return None;
         }
-        let isometry_a = Isometry2::new(
-            Vector2::new(transform_a.translation.x, transform_a.translation.y),
-            transform_a.rotation.to_euler(EulerRot::YXZ).2,
-        );
-        let isometry_b = Isometry2::new(
-            Vector2::new(transform_b.translation.x, transform_b.translation.y),
-            transform_b.rotation.to_euler(EulerRot::YXZ).2,
-        );
         query::contact(
-            &isometry_a,
+            &isometry(transform_a.translation.truncate(), transform_a.rotation),
             &collider_a.shape,
-            &isometry_b,
+            &isometry(transform_b.translation.truncate(), transform_b.rotation),
             &collider_b.shape,
             distance,
         )
@@ -89,6 +133,49 @@ impl Contact {
         .map(Self::from)
     }
 
+    /// Sweeps both colliders along the `motion` that brought them to their transforms and
+    /// returns the fraction of it after which they first touch, with the contact at that time.
+    pub fn sweep(
+        transform_a: &Transform,
+        collider_a: &Collider,
+        motion_a: Vec2,
+        transform_b: &Transform,
+        collider_b: &Collider,
+        motion_b: Vec2,
+    ) -> Option<(f32, Self)> {
+        let start_a = transform_a.translation.truncate() - motion_a;
+        let start_b = transform_b.translation.truncate() - motion_b;
+        let isometry_a = isometry(start_a, transform_a.rotation);
+        let isometry_b = isometry(start_b, transform_b.rotation);
+        let hit = query::cast_shapes(
+            &isometry_a,
+            &Vector2::new(motion_a.x, motion_a.y),
+            &collider_a.shape,
+            &isometry_b,
+            &Vector2::new(motion_b.x, motion_b.y),
+            &collider_b.shape,
+            ShapeCastOptions::with_max_time_of_impact(1.0),
+        )
+        .unwrap()?;
+        let time = hit.time_of_impact;
+        let isometry_a = isometry(start_a + motion_a * time, transform_a.rotation);
+        let isometry_b = isometry(start_b + motion_b * time, transform_b.rotation);
+        let point = |isometry: Isometry2<f32>, point: Point2<f32>| {
+            let point = isometry * point;
+            vec2(point.x, point.y)
+        };
+        let normal = isometry_a * hit.normal1.into_inner();
+        Some((
+            time,
+            Self {
+                point_a: point(isometry_a, hit.witness1),
+                point_b: point(isometry_b, hit.witness2),
+                normal: vec2(normal.x, normal.y),
+                dist: 0.0,
+            },
+        ))
+    }
+
     pub fn flip(&self) -> Self {
         Self {
             point_a: self.point_b,
@@ -99,6 +186,13 @@ impl Contact {
     }
 }
 
+fn isometry(translation: Vec2, rotation: Quat) -> Isometry2<f32> {
+    Isometry2::new(
+        Vector2::new(translation.x, translation.y),
+        rotation.to_euler(EulerRot::YXZ).2,
+    )
+}
+
 impl From<query::Contact> for Contact {
     fn from(contact: query::Contact) -> Self {
         Self {
@@ -114,16 +208,132 @@ impl From<query::Contact> for Contact {
 pub struct CollisionEvent {
     pub entity_a: Entity,
     pub entity_b: Entity,
+    /// Membership of `entity_a`
+    pub layer_a: Layer,
+    /// Membership of `entity_b`
+    pub layer_b: Layer,
     pub contact: Contact,
 }
 
+/// Reads [`CollisionEvent`]s, only passing on the ones between two layers.
+#[derive(SystemParam)]
+pub struct Collisions<'w, 's> {
+    reader: EventReader<'w, 's, CollisionEvent>,
+}
+
+impl Collisions<'_, '_> {
+    /// Collisions where `entity_a` is on `layer_a` and `entity_b` is on `layer_b`.
+    pub fn read(
+        &mut self,
+        layer_a: Layer,
+        layer_b: Layer,
+    ) -> impl Iterator<Item = &CollisionEvent> {
+        self.reader.read().filter(move |event| {
+            event.layer_a.intersects(layer_a) && event.layer_b.intersects(layer_b)
+        })
+    }
+}
+
+/// Finds the pairs of bounding circles, given as centre and radius, whose bounding boxes
+/// overlap. Sorts the boxes along the x axis and sweeps over them, so only bodies that are
+/// close on that axis get compared (sweep and prune). Pairs are `(i, j)` with `i < j`,
+/// in the same order as iterating over every combination.
+pub fn broadphase(bodies: &[(Vec2, f32)]) -> Vec<(usize, usize)> {
+    let mut order = (0..bodies.len()).collect::<Vec<_>>();
+    order.sort_unstable_by(|&a, &b| {
+        let min_x = |i: usize| bodies[i].0.x - bodies[i].1;
+        min_x(a).total_cmp(&min_x(b))
+    });
+
+    let mut pairs = vec![];
+    let mut active: Vec<usize> = vec![];
+    for index in order {
+        let (pos, radius) = bodies[index];
+        // boxes that end before this one starts can't overlap anything after it either
+        active.retain(|&other| bodies[other].0.x + bodies[other].1 >= pos.x - radius);
+        for &other in active.iter() {
+            let (other_pos, other_radius) = bodies[other];
+            if (pos.y - other_pos.y).abs() <= radius + other_radius {
+                pairs.push((index.min(other), index.max(other)));
+            }
+        }
+        active.push(index);
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
 fn do_collision(
-    colliders: Query<(Entity, &Transform, &Collider)>,
+    time: Res<Time>,
+    colliders: Query<(
+        Entity,
+        &Transform,
+        &Collider,
+        &CollisionLayers,
+        Option<&Velocity>,
+        Has<FastMover>,
+    )>,
     mut writer: EventWriter<CollisionEvent>,
 ) {
-    for [(entity_a, transform_a, collider_a), (entity_b, transform_b, collider_b)] in
-        colliders.iter_combinations()
-    {
+    let colliders = colliders.iter().collect::<Vec<_>>();
+    // how far everything moved this step, fast movers are checked along that path
+    let motions = colliders
+        .iter()
+        .map(|(_, _, _, _, velocity, _)| velocity.map_or(Vec2::ZERO, |v| v.0 * time.delta_secs()))
+        .collect::<Vec<_>>();
+    let bodies = colliders
+        .iter()
+        .zip(motions.iter())
+        .map(|((_, transform, collider, _, _, fast), motion)| {
+            let pos = transform.translation.truncate();
+            if *fast {
+                (pos - *motion * 0.5, collider.radius + motion.length() * 0.5)
+            } else {
+                (pos, collider.radius)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut write = |a: usize, b: usize, contact: Contact| {
+        let (entity_a, _, _, layers_a, _, _) = colliders[a];
+        let (entity_b, _, _, layers_b, _, _) = colliders[b];
+        writer.write(CollisionEvent {
+            entity_a: entity_b,
+            entity_b: entity_a,
+            layer_a: layers_b.membership,
+            layer_b: layers_a.membership,
+            contact: contact.flip(),
+        });
+        writer.write(CollisionEvent {
+            entity_a,
+            entity_b,
+            layer_a: layers_a.membership,
+            layer_b: layers_b.membership,
+            contact,
+        });
+    };
+
+    let mut swept = vec![];
+    for (a, b) in broadphase(&bodies) {
+        let (_, transform_a, collider_a, layers_a, _, fast_a) = colliders[a];
+        let (_, transform_b, collider_b, layers_b, _, fast_b) = colliders[b];
+        if !layers_a.interacts(layers_b) {
+            continue;
+        }
+        if fast_a || fast_b {
+            let hit = Contact::sweep(
+                transform_a,
+                collider_a,
+                motions[a],
+                transform_b,
+                collider_b,
+                motions[b],
+            );
+            if let Some((time, contact)) = hit {
+                swept.push((time, a, b, contact));
+            }
+            continue;
+        }
         let max_distance = collider_a.radius + collider_b.radius;
         let contact = Contact::query(
             transform_a,
@@ -133,16 +343,150 @@ fn do_collision(
             max_distance,
         );
         if let Some(contact) = contact.filter(|c| c.dist < 0.0) {
-            writer.write(CollisionEvent {
-                entity_a: entity_b,
-                entity_b: entity_a,
-                contact: contact.flip(),
-            });
-            writer.write(CollisionEvent {
-                entity_a,
-                entity_b,
-                contact,
-            });
+            write(a, b, contact);
+        }
+    }
+
+    // a fast mover only hits the first thing in its way
+    swept.sort_by(|(a, ..), (b, ..)| a.total_cmp(b));
+    let mut hit = vec![false; colliders.len()];
+    for (_, a, b, contact) in swept {
+        let fast = |i: usize| colliders[i].5;
+        if (fast(a) && hit[a]) || (fast(b) && hit[b]) {
+            continue;
         }
+        hit[a] = true;
+        hit[b] = true;
+        write(a, b, contact);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Compares the sweep and prune broadphase with testing every pair of colliders, for
+    //! bodies spread over a screen-sized area. Run the benchmarks with
+    //! `cargo bench collision`.
+
+    extern crate test;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use test::Bencher;
+
+    use super::*;
+    use crate::SCREEN_SIZE;
+
+    fn bodies(count: usize) -> Vec<(Transform, Collider)> {
+        let mut rng = StdRng::seed_from_u64(0);
+        (0..count)
+            .map(|_| {
+                let pos = vec2(
+                    rng.gen_range(0.0..SCREEN_SIZE.x),
+                    rng.gen_range(0.0..SCREEN_SIZE.y),
+                );
+                (
+                    Transform::from_translation(pos.extend(0.0)),
+                    Collider::from_circle(rng.gen_range(5.0..40.0)),
+                )
+            })
+            .collect()
+    }
+
+    fn circles(bodies: &[(Transform, Collider)]) -> Vec<(Vec2, f32)> {
+        bodies
+            .iter()
+            .map(|(transform, collider)| (transform.translation.truncate(), collider.radius))
+            .collect()
+    }
+
+    fn touching(bodies: &[(Transform, Collider)], a: usize, b: usize) -> bool {
+        let (transform_a, collider_a) = &bodies[a];
+        let (transform_b, collider_b) = &bodies[b];
+        Contact::query(
+            transform_a,
+            collider_a,
+            transform_b,
+            collider_b,
+            collider_a.radius + collider_b.radius,
+        )
+        .is_some_and(|contact| contact.dist < 0.0)
+    }
+
+    fn every_pair(bodies: &[(Transform, Collider)]) -> Vec<(usize, usize)> {
+        let mut pairs = vec![];
+        for a in 0..bodies.len() {
+            for b in a + 1..bodies.len() {
+                if touching(bodies, a, b) {
+                    pairs.push((a, b));
+                }
+            }
+        }
+        pairs
+    }
+
+    fn sweep_and_prune(bodies: &[(Transform, Collider)]) -> Vec<(usize, usize)> {
+        broadphase(&circles(bodies))
+            .into_iter()
+            .filter(|&(a, b)| touching(bodies, a, b))
+            .collect()
+    }
+
+    #[test]
+    fn broadphase_pairs_are_the_overlapping_boxes() {
+        let circles = circles(&bodies(300));
+        let mut overlapping = vec![];
+        for a in 0..circles.len() {
+            for b in a + 1..circles.len() {
+                let ((pos_a, radius_a), (pos_b, radius_b)) = (circles[a], circles[b]);
+                let reach = radius_a + radius_b;
+                if (pos_a - pos_b).abs().max_element() <= reach {
+                    overlapping.push((a, b));
+                }
+            }
+        }
+        assert_eq!(broadphase(&circles), overlapping);
+    }
+
+    #[test]
+    fn sweep_and_prune_finds_every_contact() {
+        for count in [0, 1, 2, 100, 500] {
+            let bodies = bodies(count);
+            assert_eq!(sweep_and_prune(&bodies), every_pair(&bodies));
+        }
+    }
+
+    #[bench]
+    fn every_pair_100(bencher: &mut Bencher) {
+        let bodies = bodies(100);
+        bencher.iter(|| every_pair(&bodies));
+    }
+
+    #[bench]
+    fn every_pair_500(bencher: &mut Bencher) {
+        let bodies = bodies(500);
+        bencher.iter(|| every_pair(&bodies));
+    }
+
+    #[bench]
+    fn every_pair_1000(bencher: &mut Bencher) {
+        let bodies = bodies(1000);
+        bencher.iter(|| every_pair(&bodies));
+    }
+
+    #[bench]
+    fn sweep_and_prune_100(bencher: &mut Bencher) {
+        let bodies = bodies(100);
+        bencher.iter(|| sweep_and_prune(&bodies));
+    }
+
+    #[bench]
+    fn sweep_and_prune_500(bencher: &mut Bencher) {
+        let bodies = bodies(500);
+        bencher.iter(|| sweep_and_prune(&bodies));
+    }
+
+    #[bench]
+    fn sweep_and_prune_1000(bencher: &mut Bencher) {
+        let bodies = bodies(1000);
+        bencher.iter(|| sweep_and_prune(&bodies));
     }
 }
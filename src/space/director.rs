@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_asset_loader::asset_collection::{AssetCollection, AssetCollectionApp};
+use serde::Deserialize;
+
+use crate::{
+    game_over::RestartGame,
+    ron_asset::{RonAsset, RonLoader},
+    scheduling::Sets,
+};
+
+use super::enemy::{enemy_spawn, Enemy, EnemyKind, EnemySpawn};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<WaveTable>()
+        .init_asset_loader::<RonLoader<WaveTable>>()
+        .init_collection::<Waves>()
+        .init_resource::<WaveDirector>()
+        .add_systems(Startup, director_ui_spawn.in_set(Sets::Spawn))
+        .add_systems(
+            Update,
+            (
+                director_update.in_set(Sets::Update),
+                director_ui_update.in_set(Sets::PostUpdate),
+            ),
+        )
+        .add_observer(director_restart);
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct Waves {
+    #[asset(path = "waves/default.waves.ron")]
+    pub table: Handle<WaveTable>,
+}
+
+/// Every wave of a run, loaded from `assets/waves/*.waves.ron`.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct WaveTable {
+    /// Played in order at the start of a run
+    pub scripted: Vec<Wave>,
+    /// Generates every wave after the scripted ones
+    pub procedural: ProceduralWaves,
+    /// Seconds after which the next wave comes even if enemies are still alive
+    pub wave_timeout: f32,
+}
+
+impl RonAsset for WaveTable {
+    const EXTENSIONS: &'static [&'static str] = &["waves.ron"];
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Wave {
+    /// Seconds of calm before the wave arrives
+    pub delay: f32,
+    pub groups: Vec<WaveGroup>,
+}
+
+/// A number of enemies of the same kind.
+#[derive(Deserialize, Clone, Debug)]
+pub struct WaveGroup {
+    #[serde(default)]
+    pub kind: EnemyKind,
+    pub count: u32,
+    /// Multiplies the health of every enemy in the group
+    #[serde(default = "one")]
+    pub health_scale: f32,
+    /// Multiplies the time between shots of every enemy in the group
+    #[serde(default = "one")]
+    pub fire_cooldown_scale: f32,
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ProceduralWaves {
+    pub delay: f32,
+    pub min_delay: f32,
+    /// The delay gets this much shorter every wave
+    pub delay_step: f32,
+    /// Stats of the first generated wave, scaled up for later ones
+    pub groups: Vec<WaveGroup>,
+    /// Enemies added to each group per wave
+    pub count_per_wave: f32,
+    /// Health added per wave, as a fraction of the group's health
+    pub health_per_wave: f32,
+    /// The fire cooldown is multiplied by this every wave
+    pub fire_cooldown_factor: f32,
+    pub min_fire_cooldown_scale: f32,
+    /// Added to every `boss_every`th generated wave
+    pub boss: WaveGroup,
+    pub boss_every: u32,
+}
+
+impl WaveTable {
+    /// The wave with the given number, starting at 1.
+    pub fn wave(&self, number: u32) -> Wave {
+        if let Some(wave) = self.scripted.get(number.saturating_sub(1) as usize) {
+            return wave.clone();
+        }
+        let procedural = &self.procedural;
+        let generated = number as usize - self.scripted.len();
+        let step = generated as f32 - 1.0;
+        let boss = generated
+            .is_multiple_of(procedural.boss_every.max(1) as usize)
+            .then_some(&procedural.boss);
+        Wave {
+            delay: (procedural.delay - procedural.delay_step * step).max(procedural.min_delay),
+            groups: procedural
+                .groups
+                .iter()
+                .map(|group| WaveGroup {
+                    kind: group.kind,
+                    count: group.count + (procedural.count_per_wave * step) as u32,
+                    health_scale: group.health_scale * (1.0 + procedural.health_per_wave * step),
+                    fire_cooldown_scale: (group.fire_cooldown_scale
+                        * procedural.fire_cooldown_factor.powf(step))
+                    .max(procedural.min_fire_cooldown_scale),
+                })
+                .chain(boss.cloned())
+                .collect(),
+        }
+    }
+}
+
+/// Decides when enemies arrive. Waits out the delay of the next wave, spawns it and
+/// moves on once it is defeated or has lasted too long.
+#[derive(Resource, Debug, Default)]
+pub struct WaveDirector {
+    /// The last wave that was spawned, 0 before the first one
+    pub wave: u32,
+    /// Counts down to the next wave, `None` while a wave is being fought
+    countdown: Option<Timer>,
+    /// Seconds the current wave has lasted
+    elapsed: f32,
+}
+
+#[derive(Component, Clone)]
+pub struct WaveDisplay;
+
+fn director_update(
+    mut commands: Commands,
+    mut director: ResMut<WaveDirector>,
+    waves: Res<Waves>,
+    tables: Res<Assets<WaveTable>>,
+    enemies: Query<(), With<Enemy>>,
+    time: Res<Time>,
+) {
+    let Some(table) = tables.get(&waves.table) else {
+        return;
+    };
+    let director = &mut *director;
+    let Some(countdown) = director.countdown.as_mut() else {
+        director.elapsed += time.delta_secs();
+        if enemies.is_empty() || director.elapsed >= table.wave_timeout {
+            let delay = table.wave(director.wave + 1).delay;
+            director.countdown = Some(Timer::new(Duration::from_secs_f32(delay), TimerMode::Once));
+        }
+        return;
+    };
+    if !countdown.tick(time.delta()).finished() {
+        return;
+    }
+    director.countdown = None;
+    director.elapsed = 0.0;
+    director.wave += 1;
+    let wave = table.wave(director.wave);
+    info!("Starting wave {}: {:?}", director.wave, wave.groups);
+    for group in wave.groups {
+        for _ in 0..group.count {
+            commands.run_system_cached_with(
+                enemy_spawn,
+                EnemySpawn {
+                    kind: group.kind,
+                    health_scale: group.health_scale,
+                    fire_cooldown_scale: group.fire_cooldown_scale,
+                },
+            );
+        }
+    }
+}
+
+fn director_ui_spawn(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Wave UI"),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(50.0),
+            top: Val::Px(10.0),
+            left: Val::ZERO,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Name::new("Wave Display"),
+            WaveDisplay,
+            Text::new(""),
+            TextLayout::new_with_justify(JustifyText::Center),
+        )],
+    ));
+}
+
+fn director_ui_update(
+    director: Res<WaveDirector>,
+    mut displays: Query<&mut Text, With<WaveDisplay>>,
+) {
+    let text = match &director.countdown {
+        Some(countdown) => format!(
+            "Wave {} in {}",
+            director.wave + 1,
+            countdown.remaining_secs().ceil()
+        ),
+        None => format!("Wave {}", director.wave),
+    };
+    for mut display in displays.iter_mut() {
+        if display.0 != text {
+            display.0 = text.clone();
+        }
+    }
+}
+
+fn director_restart(_trigger: Trigger<RestartGame>, mut commands: Commands) {
+    commands.insert_resource(WaveDirector::default());
+}
@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use bevy_asset_loader::asset_collection::{AssetCollection, AssetCollectionApp};
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use serde::Deserialize;
+
+use crate::{
+    materials::DitherMaterial,
+    resources::ResourceType,
+    rng::{GameRng, RngStream},
+    ron_asset::{RonAsset, RonLoader},
+};
+
+use super::pickup::pickup;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<DropTable>()
+        .init_asset_loader::<RonLoader<DropTable>>()
+        .init_collection::<DropTables>()
+        .add_observer(drop_loot);
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct DropTables {
+    #[asset(path = "drops/enemy.drops.ron")]
+    pub enemy: Handle<DropTable>,
+    #[asset(path = "drops/miniboss.drops.ron")]
+    pub miniboss: Handle<DropTable>,
+    #[asset(path = "drops/iron.drops.ron")]
+    pub iron: Handle<DropTable>,
+    #[asset(path = "drops/ice.drops.ron")]
+    pub ice: Handle<DropTable>,
+    #[asset(path = "drops/crystal.drops.ron")]
+    pub crystal: Handle<DropTable>,
+    #[asset(path = "drops/explosive.drops.ron")]
+    pub explosive: Handle<DropTable>,
+}
+
+/// The pickups something leaves behind, loaded from `assets/drops/*.drops.ron`.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug, Default)]
+pub struct DropTable {
+    /// Always dropped
+    #[serde(default)]
+    pub guaranteed: Vec<Loot>,
+    /// Each roll drops one of these, picked by weight
+    #[serde(default)]
+    pub weighted: Vec<WeightedLoot>,
+    /// Smallest and largest number of rolls on the weighted drops
+    #[serde(default)]
+    pub rolls: (u32, u32),
+    /// Each dropped on its own chance
+    #[serde(default)]
+    pub rare: Vec<RareLoot>,
+}
+
+impl RonAsset for DropTable {
+    const EXTENSIONS: &'static [&'static str] = &["drops.ron"];
+}
+
+/// A number of pickups of one resource.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Loot {
+    pub resource: ResourceType,
+    /// Smallest and largest number of pickups
+    #[serde(default = "one")]
+    pub count: (u32, u32),
+    /// Worth of every pickup, the resource's usual amount if not set
+    #[serde(default)]
+    pub amount: Option<f32>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct WeightedLoot {
+    pub weight: u32,
+    pub loot: Loot,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RareLoot {
+    /// From 0 to 1
+    pub chance: f64,
+    pub loot: Loot,
+}
+
+fn one() -> (u32, u32) {
+    (1, 1)
+}
+
+impl DropTable {
+    /// Picks what to drop, as the resource and worth of every pickup.
+    pub fn roll(&self, rng: &mut impl Rng) -> Vec<(ResourceType, Option<f32>)> {
+        let mut loot = self.guaranteed.iter().collect::<Vec<_>>();
+        if let Ok(weights) = WeightedIndex::new(self.weighted.iter().map(|entry| entry.weight)) {
+            for _ in 0..pick_count(self.rolls, rng) {
+                loot.push(&self.weighted[weights.sample(rng)].loot);
+            }
+        }
+        loot.extend(
+            self.rare
+                .iter()
+                .filter(|rare| rng.gen_bool(rare.chance.clamp(0.0, 1.0)))
+                .map(|rare| &rare.loot),
+        );
+        loot.into_iter()
+            .flat_map(|loot| {
+                let count = pick_count(loot.count, rng);
+                (0..count).map(|_| (loot.resource, loot.amount))
+            })
+            .collect()
+    }
+}
+
+fn pick_count((min, max): (u32, u32), rng: &mut impl Rng) -> u32 {
+    rng.gen_range(min..=max.max(min))
+}
+
+/// What an entity drops when it dies.
+#[derive(Component, Clone, Debug)]
+pub struct Drops(pub Handle<DropTable>);
+
+/// Rolls the target's [`Drops`] and spawns the pickups where it is. Trigger it right before
+/// despawning something that died.
+#[derive(Event)]
+pub struct DropLoot;
+
+fn drop_loot(
+    trigger: Trigger<DropLoot>,
+    mut commands: Commands,
+    droppers: Query<(&Drops, &Transform)>,
+    tables: Res<Assets<DropTable>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<DitherMaterial>>,
+    mut rng: ResMut<GameRng>,
+) {
+    let Ok((drops, transform)) = droppers.get(trigger.target()) else {
+        return;
+    };
+    let Some(table) = tables.get(&drops.0) else {
+        warn!("Drop table {:?} isn't loaded", drops.0.path());
+        return;
+    };
+    let rng = rng.stream(RngStream::Pickups);
+    for (resource, amount) in table.roll(rng) {
+        commands.spawn(pickup(
+            resource,
+            amount,
+            transform.translation.truncate(),
+            rng,
+            &mut meshes,
+            &mut materials,
+        ));
+    }
+}
@@ -0,0 +1,241 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::space::{
+    drops::{DropTable, DropTables},
+    health::Resistances,
+    shield::Shield,
+};
+
+use super::{
+    attack::{FirePattern, Gun, Ram},
+    steering::{AimAtShip, Chase, Orbit, Separation, Station, Steering, Wander},
+};
+
+/// The kinds of enemies waves are made of. Each combines steering behaviours and attacks
+/// from [`super::steering`] and [`super::attack`] with its own hull.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EnemyKind {
+    /// Wanders around the edge of the screen taking potshots
+    #[default]
+    Drifter,
+    /// Flies straight into the ship
+    Kamikaze,
+    /// Parks at the edge of the screen and fires fast, accurate shots
+    Sniper,
+    /// Small and weak, comes in flocks
+    Swarmer,
+    /// Slow and shielded, fires spreads
+    Tank,
+    /// Circles the ship firing rings of bullets
+    Miniboss,
+}
+
+impl EnemyKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            EnemyKind::Drifter => "Drifter",
+            EnemyKind::Kamikaze => "Kamikaze",
+            EnemyKind::Sniper => "Sniper",
+            EnemyKind::Swarmer => "Swarmer",
+            EnemyKind::Tank => "Tank",
+            EnemyKind::Miniboss => "Miniboss",
+        }
+    }
+
+    pub fn health(self) -> f32 {
+        match self {
+            EnemyKind::Drifter => 100.0,
+            EnemyKind::Kamikaze => 40.0,
+            EnemyKind::Sniper => 60.0,
+            EnemyKind::Swarmer => 20.0,
+            EnemyKind::Tank => 250.0,
+            EnemyKind::Miniboss => 800.0,
+        }
+    }
+
+    /// How hard the enemy is to push around, the ship weighs 1
+    pub fn mass(self) -> f32 {
+        match self {
+            EnemyKind::Drifter => 2.0,
+            EnemyKind::Kamikaze => 0.8,
+            EnemyKind::Sniper => 1.2,
+            EnemyKind::Swarmer => 0.3,
+            EnemyKind::Tank => 6.0,
+            EnemyKind::Miniboss => 15.0,
+        }
+    }
+
+    pub fn drop_table(self, tables: &DropTables) -> Handle<DropTable> {
+        match self {
+            EnemyKind::Miniboss => tables.miniboss.clone(),
+            _ => tables.enemy.clone(),
+        }
+    }
+
+    /// Armoured kinds shrug off some kinds of damage
+    pub fn resistances(self) -> Resistances {
+        match self {
+            EnemyKind::Tank => Resistances {
+                kinetic: 0.4,
+                impact: 0.5,
+                ..default()
+            },
+            EnemyKind::Miniboss => Resistances {
+                explosive: 0.3,
+                impact: 0.5,
+                ..default()
+            },
+            _ => Resistances::default(),
+        }
+    }
+
+    /// Radius of the shield bubble, for kinds that have a [`Shield`]
+    pub fn shield_radius(self) -> Option<f32> {
+        match self {
+            EnemyKind::Tank => Some(45.0),
+            EnemyKind::Miniboss => Some(80.0),
+            _ => None,
+        }
+    }
+
+    pub fn vertices(self) -> Vec<Vec2> {
+        match self {
+            EnemyKind::Drifter => star_hull(1.0),
+            EnemyKind::Miniboss => star_hull(2.2),
+            EnemyKind::Kamikaze => vec![
+                vec2(25.0, 0.0),
+                vec2(-15.0, 15.0),
+                vec2(-8.0, 0.0),
+                vec2(-15.0, -15.0),
+            ],
+            EnemyKind::Sniper => vec![
+                vec2(40.0, -3.0),
+                vec2(40.0, 3.0),
+                vec2(10.0, 3.0),
+                vec2(0.0, 15.0),
+                vec2(-20.0, 15.0),
+                vec2(-20.0, -15.0),
+                vec2(0.0, -15.0),
+                vec2(10.0, -3.0),
+            ],
+            EnemyKind::Swarmer => vec![
+                vec2(12.0, 0.0),
+                vec2(0.0, 8.0),
+                vec2(-10.0, 0.0),
+                vec2(0.0, -8.0),
+            ],
+            EnemyKind::Tank => (0..6)
+                .map(|i| Vec2::from_angle(std::f32::consts::TAU * i as f32 / 6.0) * 35.0)
+                .collect(),
+        }
+    }
+
+    /// Adds the movement and attack components of this kind. `spot` is a random point
+    /// near the edge of the screen, `fire_cooldown_scale` stretches the gun's cooldown.
+    /// The first shot comes after one to two cooldowns, so a wave doesn't fire all at once.
+    pub fn insert_behaviour(
+        self,
+        entity: &mut EntityCommands,
+        spot: Vec2,
+        fire_cooldown_scale: f32,
+        rng: &mut impl Rng,
+    ) {
+        let mut gun = |pattern, cooldown: f32| {
+            let gun = Gun::new(pattern, cooldown * fire_cooldown_scale);
+            Gun {
+                timer: gun.cooldown * rng.gen_range(1.0..2.0),
+                ..gun
+            }
+        };
+        match self {
+            EnemyKind::Drifter => entity.insert((
+                Steering::new(300.0, 1.0),
+                Wander { target: spot },
+                AimAtShip {
+                    lead: 0.5,
+                    turn_speed: 4.0,
+                },
+                gun(FirePattern::Single, 5.0),
+            )),
+            EnemyKind::Kamikaze => entity.insert((
+                Steering::new(450.0, 2.5),
+                Chase { lead: 0.3 },
+                AimAtShip {
+                    lead: 0.3,
+                    turn_speed: 8.0,
+                },
+                Ram { damage: 25.0 },
+            )),
+            EnemyKind::Sniper => entity.insert((
+                Steering::new(200.0, 1.5),
+                Station { spot },
+                AimAtShip {
+                    lead: 1.0,
+                    turn_speed: 2.0,
+                },
+                gun(FirePattern::Single, 4.0).with_bullet(1400.0, 20.0, 8.0),
+            )),
+            EnemyKind::Swarmer => entity.insert((
+                Steering::new(380.0, 3.0),
+                Chase { lead: 0.0 },
+                Separation { radius: 60.0 },
+                AimAtShip {
+                    lead: 0.2,
+                    turn_speed: 6.0,
+                },
+                gun(FirePattern::Single, 2.5).with_bullet(600.0, 4.0, 8.0),
+            )),
+            EnemyKind::Tank => entity.insert((
+                Steering::new(120.0, 0.5),
+                Wander { target: spot },
+                AimAtShip {
+                    lead: 0.5,
+                    turn_speed: 1.5,
+                },
+                gun(
+                    FirePattern::Spread {
+                        count: 3,
+                        angle: 20.0,
+                    },
+                    6.0,
+                )
+                .with_bullet(500.0, 10.0, 15.0),
+                Shield::new(100.0, 10.0),
+            )),
+            EnemyKind::Miniboss => entity.insert((
+                Steering::new(180.0, 0.8),
+                Orbit { radius: 350.0 },
+                AimAtShip {
+                    lead: 0.5,
+                    turn_speed: 2.0,
+                },
+                gun(FirePattern::Ring { count: 12 }, 4.0).with_bullet(450.0, 10.0, 12.0),
+                Shield::new(200.0, 15.0),
+            )),
+        };
+    }
+}
+
+/// The original enemy hull, a square with spikes
+fn star_hull(scale: f32) -> Vec<Vec2> {
+    const OUTER: f32 = 30.0;
+    const INNER: f32 = 20.0;
+    const MID: f32 = (INNER + OUTER) / 2.0;
+    [
+        vec2(INNER, -INNER),        // top right
+        vec2(-INNER, -INNER * 0.8), // top left
+        vec2(-INNER, INNER * 0.8),  // bottom left
+        vec2(INNER, INNER),         // bottom right
+        vec2(OUTER, MID),
+        vec2(0.0, OUTER),
+        vec2(-OUTER, MID),
+        vec2(-MID, 0.0),
+        vec2(-OUTER, -MID),
+        vec2(0.0, -OUTER),
+        vec2(OUTER, -MID),
+    ]
+    .map(|vertex| vertex * scale)
+    .to_vec()
+}
@@ -0,0 +1,164 @@
+use bevy::{ecs::error, prelude::*};
+
+use crate::{
+    layers::SpaceLayer,
+    materials::{DitherMaterial, MetalDither},
+    scheduling::Sets,
+    sounds::Sounds,
+    space::{
+        collision::{Collider, CollisionLayers, Collisions, FastMover, Layer},
+        health::{damage, kill, Damage, Health},
+        particles::EmitParticles,
+        physics::{DespawnOutOfBounds, Rotation, Velocity},
+        ship::Ship,
+    },
+    z_order::ZOrder,
+};
+
+use super::{Enemy, EnemyBullet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, (gun_fire, ram_collide).in_set(Sets::Update));
+}
+
+/// Shoots `pattern` in the direction the enemy faces every `cooldown` seconds.
+#[derive(Component, Clone, Debug)]
+pub struct Gun {
+    pub pattern: FirePattern,
+    pub cooldown: f32,
+    /// Seconds until the next shot
+    pub timer: f32,
+    pub bullet_speed: f32,
+    pub bullet_damage: f32,
+    pub bullet_size: f32,
+}
+
+impl Gun {
+    pub fn new(pattern: FirePattern, cooldown: f32) -> Self {
+        Self {
+            pattern,
+            cooldown,
+            // enemies spawn off-screen, they shouldn't open fire before they are in view
+            timer: cooldown,
+            bullet_speed: 700.0,
+            bullet_damage: 10.0,
+            bullet_size: 15.0,
+        }
+    }
+
+    pub fn with_bullet(self, speed: f32, damage: f32, size: f32) -> Self {
+        Self {
+            bullet_speed: speed,
+            bullet_damage: damage,
+            bullet_size: size,
+            ..self
+        }
+    }
+
+    /// Directions of one volley, in radians
+    fn angles(&self, facing: f32) -> Vec<f32> {
+        match self.pattern {
+            FirePattern::Single => vec![facing],
+            FirePattern::Spread { count, angle } => {
+                let step = angle.to_radians();
+                let start = facing - step * (count - 1) as f32 / 2.0;
+                (0..count).map(|i| start + step * i as f32).collect()
+            }
+            FirePattern::Ring { count } => (0..count)
+                .map(|i| facing + std::f32::consts::TAU * i as f32 / count as f32)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum FirePattern {
+    Single,
+    /// `count` bullets fanned out `angle` degrees apart
+    Spread {
+        count: u32,
+        angle: f32,
+    },
+    /// `count` bullets evenly around the enemy
+    Ring {
+        count: u32,
+    },
+}
+
+/// Flies into the ship, dealing `damage` and destroying itself.
+#[derive(Component, Clone, Debug)]
+pub struct Ram {
+    pub damage: f32,
+}
+
+fn gun_fire(
+    mut commands: Commands,
+    mut enemies: Query<(&Transform, &mut Gun), With<Enemy>>,
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<DitherMaterial>>,
+    sounds: Res<Sounds>,
+) {
+    for (transform, mut gun) in enemies.iter_mut() {
+        if gun.timer > 0.0 {
+            gun.timer -= time.delta_secs();
+            continue;
+        }
+        gun.timer = gun.cooldown;
+        let facing = transform.rotation.to_euler(EulerRot::YXZ).2;
+        for angle in gun.angles(facing) {
+            commands.spawn((
+                Name::new("Enemy Bullet"),
+                SpaceLayer,
+                EnemyBullet(Damage::kinetic(gun.bullet_damage)),
+                Mesh2d(meshes.add(Ellipse::from_size(vec2(1.0, 0.8) * gun.bullet_size))),
+                MeshMaterial2d(materials.add(MetalDither {
+                    fill: 0.5,
+                    scale: 10.0,
+                })),
+                Collider::from_circle(gun.bullet_size),
+                CollisionLayers::new(Layer::ENEMY_PROJECTILE, Layer::SHIP),
+                FastMover,
+                DespawnOutOfBounds,
+                Transform::from_translation(transform.translation),
+                ZOrder::BULLET,
+                Velocity(Vec2::from_angle(angle) * gun.bullet_speed),
+                Rotation(angle.to_degrees()),
+            ));
+        }
+        commands.spawn((
+            Name::new("Enemy Shoot Sound"),
+            AudioPlayer::new(sounds.enemy_gun.clone()),
+            PlaybackSettings::DESPAWN,
+        ));
+    }
+}
+
+fn ram_collide(
+    mut commands: Commands,
+    mut collisions: Collisions,
+    rams: Query<(&Ram, &Health), With<Enemy>>,
+    ship: Single<Entity, With<Ship>>,
+    mut particle_writer: EventWriter<EmitParticles>,
+) {
+    for event in collisions.read(Layer::ENEMY, Layer::SHIP) {
+        if event.entity_b != *ship {
+            continue;
+        }
+        if let Ok((ram, health)) = rams.get(event.entity_a) {
+            if health.is_dead() {
+                continue;
+            }
+            commands
+                .entity(*ship)
+                .queue_handled(damage(Damage::impact(ram.damage)), error::ignore);
+            commands
+                .entity(event.entity_a)
+                .queue_handled(kill(), error::ignore);
+            particle_writer.write(EmitParticles {
+                position: event.contact.point_b,
+                count: 10,
+            });
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use bevy::{audio::PlaybackMode, ecs::error, prelude::*};
+
+use crate::{
+    game_over::RestartGame,
+    layers::SpaceLayer,
+    materials::{DitherMaterial, MetalDither},
+    mesh::MeshLyonExtensions,
+    rng::{GameRng, RngStream},
+    scheduling::Sets,
+    sounds::Sounds,
+    z_order::ZOrder,
+};
+
+use super::{
+    bounds::ScreenBounds,
+    collision::{Collider, CollisionLayers, Collisions, Layer},
+    drops::{DropLoot, DropTables, Drops},
+    health::{damage, Damage, Death, Health},
+    particles::EmitParticles,
+    physics::Velocity,
+    rigid_body::Mass,
+    shield::shield_bubble,
+    ship::Ship,
+};
+
+mod archetype;
+mod attack;
+mod steering;
+
+pub use archetype::EnemyKind;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((attack::plugin, steering::plugin))
+        .add_systems(Startup, enemy_sound_spawn.in_set(Sets::Spawn))
+        .add_systems(FixedUpdate, enemy_bullet_collide.in_set(Sets::Update))
+        .add_observer(enemy_die)
+        .add_observer(enemy_sound_restart);
+}
+
+#[derive(Component, Clone)]
+pub struct Enemy;
+
+/// Loops while any enemy is alive. There is only one, however many enemies there are.
+#[derive(Component, Clone)]
+pub struct EnemyLivingSound;
+
+fn enemy_sound_spawn(mut commands: Commands, sounds: Res<Sounds>) {
+    commands.spawn((
+        Name::new("Enemy Living Sound"),
+        EnemyLivingSound,
+        AudioPlayer::new(sounds.enemy_gamer.clone()),
+        PlaybackSettings {
+            mode: PlaybackMode::Loop,
+            paused: true,
+            ..default()
+        },
+    ));
+}
+
+/// Enemies are despawned without dying on a restart
+fn enemy_sound_restart(
+    _trigger: Trigger<RestartGame>,
+    enemy_living_sound: Option<Single<&AudioSink, With<EnemyLivingSound>>>,
+) {
+    if let Some(sound) = enemy_living_sound {
+        sound.pause();
+    }
+}
+
+/// What to spawn, chosen by the wave director.
+#[derive(Clone, Debug)]
+pub struct EnemySpawn {
+    pub kind: EnemyKind,
+    /// Multiplies the kind's health
+    pub health_scale: f32,
+    /// Multiplies the kind's time between shots
+    pub fire_cooldown_scale: f32,
+}
+
+pub(super) fn enemy_spawn(
+    In(spawn): In<EnemySpawn>,
+    mut commands: Commands,
+    enemy_living_sound: Option<Single<&AudioSink, With<EnemyLivingSound>>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<DitherMaterial>>,
+    bounds: ScreenBounds,
+    mut rng: ResMut<GameRng>,
+    drop_tables: Res<DropTables>,
+) {
+    let rng = rng.stream(RngStream::Enemies);
+    let kind = spawn.kind;
+    let vertices = kind.vertices();
+    let mut enemy = commands.spawn((
+        Name::new(kind.name()),
+        SpaceLayer,
+        Enemy,
+        Health::new(kind.health() * spawn.health_scale),
+        kind.resistances(),
+        Mesh2d(meshes.add(Mesh::fill_polygon(&vertices))),
+        MeshMaterial2d(materials.add(MetalDither {
+            fill: 1.0,
+            scale: 20.0,
+        })),
+        Collider::from_vertices(&vertices),
+        CollisionLayers::new(
+            Layer::ENEMY,
+            Layer::SHIP | Layer::SHIP_PROJECTILE | Layer::ENEMY | Layer::ASTEROID,
+        ),
+        Transform::from_translation(bounds.random_outside(rng).extend(0.0) * 1.2),
+        ZOrder::ENEMY,
+        Velocity(Vec2::ZERO),
+        Mass(kind.mass()),
+        Drops(kind.drop_table(&drop_tables)),
+    ));
+    kind.insert_behaviour(
+        &mut enemy,
+        bounds.random_outside(rng) * 0.8,
+        spawn.fire_cooldown_scale,
+        rng,
+    );
+    if let Some(radius) = kind.shield_radius() {
+        enemy.with_child(shield_bubble(radius, &mut meshes, &mut materials));
+    }
+    if let Some(sound) = enemy_living_sound {
+        sound.play();
+    }
+}
+
+#[derive(Component, Clone)]
+pub struct EnemyBullet(pub Damage);
+
+fn enemy_bullet_collide(
+    mut commands: Commands,
+    mut collisions: Collisions,
+    bullets: Query<&EnemyBullet>,
+    ship: Single<Entity, With<Ship>>,
+    mut particle_writer: EventWriter<EmitParticles>,
+) {
+    for event in collisions.read(Layer::ENEMY_PROJECTILE, Layer::SHIP) {
+        if let Ok(bullet) = bullets.get(event.entity_a) {
+            if event.entity_b == *ship {
+                // Handle collision between bullet and ship
+                commands.entity(event.entity_a).despawn();
+                commands
+                    .entity(*ship)
+                    .queue_handled(damage(bullet.0), error::ignore);
+                particle_writer.write(EmitParticles {
+                    position: event.contact.point_b,
+                    count: 3,
+                });
+            }
+        }
+    }
+}
+
+fn enemy_die(
+    trigger: Trigger<Death>,
+    mut commands: Commands,
+    enemies: Query<(Entity, &Health, &Transform), With<Enemy>>,
+    enemy_living_sound: Option<Single<&AudioSink, With<EnemyLivingSound>>>,
+    sounds: Res<Sounds>,
+    mut particle_writer: EventWriter<EmitParticles>,
+) {
+    let entity = trigger.target();
+    let Ok((_, _, transform)) = enemies.get(entity) else {
+        return;
+    };
+    commands.trigger_targets(DropLoot, entity);
+    commands.entity(entity).despawn();
+    particle_writer.write(EmitParticles {
+        position: transform.translation.truncate(),
+        count: 10,
+    });
+    let last_alive = enemies
+        .iter()
+        .all(|(other, health, _)| other == entity || health.is_dead());
+    if let Some(sound) = enemy_living_sound.filter(|_| last_alive) {
+        sound.pause();
+    }
+    commands.spawn((
+        Name::new("Enemy Die Sound"),
+        AudioPlayer::new(sounds.enemy_die.clone()),
+        PlaybackSettings::DESPAWN,
+    ));
+}
@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+
+use crate::{
+    rng::{GameRng, RngStream},
+    scheduling::Sets,
+    space::{bounds::ScreenBounds, physics::Velocity, ship::Ship},
+};
+
+use super::Enemy;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (
+            (
+                steer_wander,
+                steer_chase,
+                steer_station,
+                steer_orbit,
+                steer_separate,
+            ),
+            steering_apply,
+            steer_aim,
+        )
+            .chain()
+            .in_set(Sets::Input),
+    );
+}
+
+/// Shared movement of every enemy. Behaviours add up the velocity they would like in
+/// `desired`, which the enemy then accelerates toward.
+#[derive(Component, Clone, Debug)]
+pub struct Steering {
+    pub max_speed: f32,
+    /// How quickly the velocity follows the desired one
+    pub agility: f32,
+    pub desired: Vec2,
+}
+
+impl Steering {
+    pub fn new(max_speed: f32, agility: f32) -> Self {
+        Self {
+            max_speed,
+            agility,
+            desired: Vec2::ZERO,
+        }
+    }
+
+    /// Adds `direction` scaled to the top speed to the desired velocity.
+    pub fn seek(&mut self, direction: Vec2) {
+        self.desired += direction * self.max_speed;
+    }
+}
+
+/// Flies between random points near the edge of the screen.
+#[derive(Component, Clone, Debug)]
+#[require(Steering::new(300.0, 1.0))]
+pub struct Wander {
+    pub target: Vec2,
+}
+
+/// Flies straight at where the ship will be in `lead` seconds.
+#[derive(Component, Clone, Debug)]
+#[require(Steering::new(300.0, 1.0))]
+pub struct Chase {
+    pub lead: f32,
+}
+
+/// Flies to `spot` and holds position there.
+#[derive(Component, Clone, Debug)]
+#[require(Steering::new(300.0, 1.0))]
+pub struct Station {
+    pub spot: Vec2,
+}
+
+/// Circles the ship at `radius`.
+#[derive(Component, Clone, Debug)]
+#[require(Steering::new(300.0, 1.0))]
+pub struct Orbit {
+    pub radius: f32,
+}
+
+/// Keeps away from other separating enemies closer than `radius`.
+#[derive(Component, Clone, Debug)]
+#[require(Steering::new(300.0, 1.0))]
+pub struct Separation {
+    pub radius: f32,
+}
+
+/// Turns to face where the ship will be in `lead` seconds.
+#[derive(Component, Clone, Debug)]
+pub struct AimAtShip {
+    pub lead: f32,
+    pub turn_speed: f32,
+}
+
+/// Distance at which stationed enemies start braking
+const ARRIVE_RADIUS: f32 = 100.0;
+
+fn ship_prediction((transform, velocity): (&Transform, &Velocity), lead: f32) -> Vec2 {
+    transform.translation.truncate() + velocity.0 * lead
+}
+
+fn steer_wander(
+    mut enemies: Query<(&Transform, &mut Wander, &mut Steering)>,
+    bounds: ScreenBounds,
+    mut rng: ResMut<GameRng>,
+) {
+    for (transform, mut wander, mut steering) in enemies.iter_mut() {
+        let diff = wander.target - transform.translation.truncate();
+        steering.seek(diff.normalize_or_zero());
+        if diff.length() < 10.0 {
+            wander.target = bounds.random_outside(rng.stream(RngStream::Enemies)) * 0.8;
+        }
+    }
+}
+
+fn steer_chase(
+    mut enemies: Query<(&Transform, &Chase, &mut Steering)>,
+    ship: Single<(&Transform, &Velocity), (With<Ship>, Without<Enemy>)>,
+) {
+    for (transform, chase, mut steering) in enemies.iter_mut() {
+        let diff = ship_prediction(*ship, chase.lead) - transform.translation.truncate();
+        steering.seek(diff.normalize_or_zero());
+    }
+}
+
+fn steer_station(mut enemies: Query<(&Transform, &Station, &mut Steering)>) {
+    for (transform, station, mut steering) in enemies.iter_mut() {
+        let diff = station.spot - transform.translation.truncate();
+        steering.seek(diff.normalize_or_zero() * (diff.length() / ARRIVE_RADIUS).min(1.0));
+    }
+}
+
+fn steer_orbit(
+    mut enemies: Query<(&Transform, &Orbit, &mut Steering)>,
+    ship: Single<&Transform, (With<Ship>, Without<Enemy>)>,
+) {
+    for (transform, orbit, mut steering) in enemies.iter_mut() {
+        let offset = transform.translation.truncate() - ship.translation.truncate();
+        let outward = offset.normalize_or_zero();
+        // push back onto the circle, then go around it
+        let correction = outward * (orbit.radius - offset.length()) / ARRIVE_RADIUS;
+        let desired = (outward.perp() + correction).normalize_or_zero();
+        steering.seek(desired);
+    }
+}
+
+fn steer_separate(mut enemies: Query<(&Transform, &Separation, &mut Steering)>) {
+    let positions = enemies
+        .iter()
+        .map(|(transform, _, _)| transform.translation.truncate())
+        .collect::<Vec<_>>();
+    for (transform, separation, mut steering) in enemies.iter_mut() {
+        let pos = transform.translation.truncate();
+        let push = positions
+            .iter()
+            .map(|other| pos - *other)
+            .filter(|diff| *diff != Vec2::ZERO && diff.length() < separation.radius)
+            .map(|diff| diff.normalize() * (1.0 - diff.length() / separation.radius))
+            .sum::<Vec2>();
+        steering.seek(push);
+    }
+}
+
+fn steering_apply(mut enemies: Query<(&mut Steering, &mut Velocity)>, time: Res<Time>) {
+    for (mut steering, mut velocity) in enemies.iter_mut() {
+        let desired = std::mem::take(&mut steering.desired).clamp_length_max(steering.max_speed);
+        velocity.0 = velocity
+            .0
+            .lerp(desired, (steering.agility * time.delta_secs()).min(1.0));
+    }
+}
+
+fn steer_aim(
+    mut enemies: Query<(&mut Transform, &AimAtShip)>,
+    ship: Single<(&Transform, &Velocity), (With<Ship>, Without<AimAtShip>)>,
+    time: Res<Time>,
+) {
+    for (mut transform, aim) in enemies.iter_mut() {
+        let target_rotation =
+            (ship_prediction(*ship, aim.lead) - transform.translation.truncate()).to_angle();
+        transform.rotation = transform.rotation.slerp(
+            Quat::from_rotation_z(target_rotation),
+            time.delta_secs() * aim.turn_speed,
+        );
+    }
+}
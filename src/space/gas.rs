@@ -9,6 +9,7 @@ use crate::{
     materials::{DitherMaterial, RockyDither},
     mesh::MeshLyonExtensions,
     resources::{ResourceType, Resources},
+    rng::{GameRng, RngStream},
     scheduling::Sets,
     sounds::Sounds,
     z_order::ZOrder,
@@ -16,20 +17,20 @@ use crate::{
 
 use super::{
     bounds::ScreenBounds,
-    collision::{Collider, CollisionEvent},
+    collision::{Collider, CollisionLayers, Collisions, Layer},
     physics::{Spin, Velocity},
     ship::Ship,
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(
-        Update,
-        (
-            gas_spawn_timer.in_set(Sets::PreUpdate),
-            gas_succ.in_set(Sets::Update),
-            gas_dissipate.in_set(Sets::PostUpdate),
-        ),
-    );
+    app.add_systems(FixedUpdate, gas_succ.in_set(Sets::Update))
+        .add_systems(
+            Update,
+            (
+                gas_spawn_timer.in_set(Sets::PreUpdate),
+                gas_dissipate.in_set(Sets::PostUpdate),
+            ),
+        );
 }
 
 #[derive(Component, Clone)]
@@ -65,11 +66,12 @@ fn spawn_cloud(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<DitherMaterial>>,
     bounds: ScreenBounds,
+    mut rng: ResMut<GameRng>,
 ) {
-    let pos = bounds.random_outside() * 1.2;
-    let target = bounds.random_inside() * 0.8;
+    let rng = rng.stream(RngStream::Gas);
+    let pos = bounds.random_outside(rng) * 1.2;
+    let target = bounds.random_inside(rng) * 0.8;
     let mesh = Mesh::fill_with(|builder| {
-        let mut rng = rand::thread_rng();
         builder.add_circle(
             Point2D::new(rng.gen_range(-5.0..5.0), rng.gen_range(-5.0..5.0)),
             40.0,
@@ -91,12 +93,14 @@ fn spawn_cloud(
         MeshMaterial2d(materials.add(RockyDither {
             fill: 0.5,
             scale: 100.0,
+            offset: vec2(rng.gen(), rng.gen()),
         })),
         Transform::from_xyz(pos.x, pos.y, 0.0),
         ZOrder::ASTEROID,
         Collider::from_circle(60.0),
-        Velocity::random_towards(target - pos, 30.0..70.0),
-        Spin::random(30.0),
+        CollisionLayers::new(Layer::GAS, Layer::SHIP),
+        Velocity::random_towards(rng, target - pos, 30.0..70.0),
+        Spin::random(rng, 30.0),
     ));
 }
 
@@ -105,7 +109,7 @@ pub struct SuccSound;
 
 fn gas_succ(
     mut commands: Commands,
-    mut collision_events: EventReader<CollisionEvent>,
+    mut collisions: Collisions,
     clouds: Query<&GasCloud>,
     ships: Query<Entity, With<Ship>>,
     mut resources: ResMut<Resources>,
@@ -114,7 +118,7 @@ fn gas_succ(
     time: Res<Time>,
 ) {
     let mut is_succ = false;
-    for event in collision_events.read() {
+    for event in collisions.read(Layer::GAS, Layer::SHIP) {
         if let Ok(cloud) = clouds.get(event.entity_a) {
             if let Ok(ship) = ships.get(event.entity_b) {
                 let succ_amount = (time.delta_secs() * 2.5).min(cloud.remaining);
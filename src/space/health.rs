@@ -0,0 +1,236 @@
+use bevy::prelude::*;
+
+use crate::scheduling::Sets;
+
+use super::shield::{Shield, ShieldBreak};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, invulnerable_tick.in_set(Sets::PreUpdate));
+}
+
+/// Hit points of anything that can be destroyed. Change it through [`damage`], [`heal`] and
+/// [`kill`] so shields, resistances and [`Death`] are taken care of. Queue them with
+/// `queue_handled(.., error::ignore)`, an earlier hit in the same frame may have despawned it.
+#[derive(Component, Clone, Debug)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        (self.current / self.max).clamp(0.0, 1.0)
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DamageKind {
+    /// Bullets and slugs
+    Kinetic,
+    /// Rockets, mines and blasts
+    Explosive,
+    /// Beams, dealt a little every frame
+    Beam,
+    /// Bumping into things
+    Impact,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Damage {
+    pub amount: f32,
+    pub kind: DamageKind,
+}
+
+impl Damage {
+    pub fn kinetic(amount: f32) -> Self {
+        Self {
+            amount,
+            kind: DamageKind::Kinetic,
+        }
+    }
+
+    pub fn explosive(amount: f32) -> Self {
+        Self {
+            amount,
+            kind: DamageKind::Explosive,
+        }
+    }
+
+    pub fn beam(amount: f32) -> Self {
+        Self {
+            amount,
+            kind: DamageKind::Beam,
+        }
+    }
+
+    pub fn impact(amount: f32) -> Self {
+        Self {
+            amount,
+            kind: DamageKind::Impact,
+        }
+    }
+}
+
+/// Share of each kind of damage shrugged off, from 0 to 1
+#[derive(Component, Clone, Debug, Default)]
+pub struct Resistances {
+    pub kinetic: f32,
+    pub explosive: f32,
+    pub beam: f32,
+    pub impact: f32,
+}
+
+impl Resistances {
+    pub fn get(&self, kind: DamageKind) -> f32 {
+        match kind {
+            DamageKind::Kinetic => self.kinetic,
+            DamageKind::Explosive => self.explosive,
+            DamageKind::Beam => self.beam,
+            DamageKind::Impact => self.impact,
+        }
+    }
+}
+
+/// Ignores all damage for `duration` seconds after every hit.
+#[derive(Component, Clone, Debug)]
+pub struct Invulnerable {
+    pub duration: f32,
+    remaining: f32,
+}
+
+impl Invulnerable {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            remaining: 0.0,
+        }
+    }
+}
+
+/// Triggered on an entity after it got hurt, with how much its health went down.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageEvent {
+    pub damage: Damage,
+    pub dealt: f32,
+}
+
+/// Triggered on an entity once, when its health runs out. Whatever it is decides how to die.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Death;
+
+/// Hurts an entity. Resistances and then its [`Shield`] take their share first.
+pub fn damage(damage: Damage) -> impl EntityCommand {
+    move |mut entity: EntityWorldMut| {
+        if let Some(mut invulnerable) = entity.get_mut::<Invulnerable>() {
+            if invulnerable.remaining > 0.0 {
+                return;
+            }
+            invulnerable.remaining = invulnerable.duration;
+        }
+        let resistance = entity
+            .get::<Resistances>()
+            .map_or(0.0, |resistances| resistances.get(damage.kind));
+        let mut amount = damage.amount * (1.0 - resistance.clamp(0.0, 1.0));
+        if let Some(mut shield) = entity.get_mut::<Shield>() {
+            let was_up = shield.strength > 0.0;
+            amount = shield.absorb(amount);
+            if was_up && shield.strength <= 0.0 {
+                let target = entity.id();
+                entity.world_scope(|world| world.trigger_targets(ShieldBreak, target));
+            }
+        }
+        hurt(&mut entity, amount, damage);
+    }
+}
+
+/// Kills an entity outright, whatever protects it.
+pub fn kill() -> impl EntityCommand {
+    move |mut entity: EntityWorldMut| {
+        let Some(current) = entity.get::<Health>().map(|health| health.current) else {
+            return;
+        };
+        hurt(&mut entity, current.max(0.0), Damage::impact(current));
+    }
+}
+
+/// Restores health, up to the maximum.
+pub fn heal(amount: f32) -> impl EntityCommand {
+    move |mut entity: EntityWorldMut| {
+        if let Some(mut health) = entity.get_mut::<Health>() {
+            if !health.is_dead() {
+                health.current = (health.current + amount).min(health.max);
+            }
+        }
+    }
+}
+
+fn hurt(entity: &mut EntityWorldMut, amount: f32, damage: Damage) {
+    let Some(mut health) = entity.get_mut::<Health>() else {
+        return;
+    };
+    if health.is_dead() {
+        return;
+    }
+    health.current -= amount;
+    let died = health.is_dead();
+    let target = entity.id();
+    entity.world_scope(|world| {
+        world.trigger_targets(
+            DamageEvent {
+                damage,
+                dealt: amount,
+            },
+            target,
+        );
+        if died {
+            world.trigger_targets(Death, target);
+        }
+    });
+}
+
+fn invulnerable_tick(mut query: Query<&mut Invulnerable>, time: Res<Time>) {
+    for mut invulnerable in query.iter_mut() {
+        if invulnerable.remaining > 0.0 {
+            invulnerable.remaining -= time.delta_secs();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::error;
+
+    use super::*;
+
+    #[test]
+    fn hits_after_death_are_ignored() {
+        let mut world = World::new();
+        world.add_observer(|trigger: Trigger<Death>, mut commands: Commands| {
+            commands.entity(trigger.target()).despawn();
+        });
+        let target = world.spawn(Health::new(10.0)).id();
+
+        // two bullets land in the same frame, the first one is enough
+        let mut commands = world.commands();
+        for _ in 0..2 {
+            commands
+                .entity(target)
+                .queue_handled(damage(Damage::kinetic(10.0)), error::ignore);
+        }
+        commands.entity(target).queue_handled(kill(), error::ignore);
+        commands
+            .entity(target)
+            .queue_handled(heal(10.0), error::ignore);
+        world.flush();
+
+        assert!(world.get_entity(target).is_err());
+    }
+}
@@ -5,13 +5,19 @@ mod bg;
 mod bounds;
 mod camera;
 mod collision;
+mod director;
+mod drops;
 mod enemy;
 mod gas;
+mod health;
 mod particles;
 mod physics;
 mod pickup;
 mod restart;
+mod rigid_body;
+mod shield;
 mod ship;
+mod weapon;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
@@ -19,12 +25,20 @@ pub(super) fn plugin(app: &mut App) {
         bg::plugin,
         camera::plugin,
         collision::plugin,
+        director::plugin,
+        drops::plugin,
         enemy::plugin,
         gas::plugin,
         particles::plugin,
         physics::plugin,
         pickup::plugin,
         restart::plugin,
+    ))
+    .add_plugins((
+        health::plugin,
+        rigid_body::plugin,
+        shield::plugin,
         ship::plugin,
+        weapon::plugin,
     ));
 }
@@ -1,8 +1,10 @@
 use bevy::prelude::*;
+use rand::Rng;
 
 use crate::{
     layers::SpaceLayer,
     materials::{DitherMaterial, GassyDither},
+    rng::{GameRng, RngStream},
     scheduling::Sets,
     z_order::ZOrder,
 };
@@ -34,7 +36,9 @@ pub fn particle_emit(
     mut reader: EventReader<EmitParticles>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<DitherMaterial>>,
+    mut rng: ResMut<GameRng>,
 ) {
+    let rng = rng.stream(RngStream::Particles);
     let mesh = meshes.add(Circle::new(10.0));
     for event in reader.read() {
         for _ in 0..event.count {
@@ -44,13 +48,13 @@ pub fn particle_emit(
                 SpaceLayer,
                 Mesh2d(mesh.clone()),
                 MeshMaterial2d(materials.add(GassyDither {
-                    fill: rand::random::<f32>() * 0.3 + 0.4,
+                    fill: rng.gen::<f32>() * 0.3 + 0.4,
                     scale: 20.0,
                 })),
                 Transform::from_translation(event.position.extend(0.0)),
                 ZOrder::BULLET,
-                Velocity::random(0.0..100.0),
-                Rotation::random(),
+                Velocity::random(rng, 0.0..100.0),
+                Rotation::random(rng),
             ));
         }
     }
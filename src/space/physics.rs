@@ -1,6 +1,6 @@
 use std::ops::Range;
 
-use bevy::prelude::*;
+use bevy::{app::RunFixedMainLoopSystem, prelude::*};
 use rand::Rng;
 
 use crate::scheduling::Sets;
@@ -9,31 +9,36 @@ use super::camera::SpaceCamera;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(
-        Update,
+        FixedUpdate,
         (
             (physics_move, physics_spin).in_set(Sets::Physics),
             physics_rotation.in_set(Sets::PostUpdate),
         ),
-    );
+    )
+    .add_systems(
+        RunFixedMainLoop,
+        (
+            interpolation_restore.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+            interpolation_apply.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+        ),
+    )
+    .add_systems(FixedFirst, interpolation_start);
 }
 
 #[derive(Component, Clone)]
+#[require(Interpolated)]
 pub struct Velocity(pub Vec2);
 
 impl Velocity {
-    pub fn random(speed: Range<f32>) -> Self {
+    pub fn random(rng: &mut impl Rng, speed: Range<f32>) -> Self {
         Self(
-            Vec2::new(
-                rand::random::<f32>() * 2.0 - 1.0,
-                rand::random::<f32>() * 2.0 - 1.0,
-            )
-            .normalize()
-                * rand::thread_rng().gen_range(speed),
+            Vec2::new(rng.gen::<f32>() * 2.0 - 1.0, rng.gen::<f32>() * 2.0 - 1.0).normalize()
+                * rng.gen_range(speed),
         )
     }
 
-    pub fn random_towards(dir: Vec2, speed: Range<f32>) -> Self {
-        Self(dir.normalize() * rand::thread_rng().gen_range(speed))
+    pub fn random_towards(rng: &mut impl Rng, dir: Vec2, speed: Range<f32>) -> Self {
+        Self(dir.normalize() * rng.gen_range(speed))
     }
 }
 
@@ -41,8 +46,8 @@ impl Velocity {
 pub struct Rotation(pub f32);
 
 impl Rotation {
-    pub fn random() -> Self {
-        Self(rand::random::<f32>() * 360.0)
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self(rng.gen::<f32>() * 360.0)
     }
 }
 
@@ -51,8 +56,8 @@ impl Rotation {
 pub struct Spin(pub f32);
 
 impl Spin {
-    pub fn random(range: f32) -> Self {
-        Self(rand::random::<f32>() * range * 2.0 - range)
+    pub fn random(rng: &mut impl Rng, range: f32) -> Self {
+        Self(rng.gen::<f32>() * range * 2.0 - range)
     }
 }
 
@@ -114,3 +119,53 @@ fn physics_rotation(mut query: Query<(&mut Transform, &Rotation)>) {
         transform.rotation = Quat::from_rotation_z(rotation.0.to_radians());
     }
 }
+
+/// Smooths out movement between fixed steps. Outside of `FixedUpdate` the transform is drawn
+/// between where the last step started and ended, inside it holds the simulated state again.
+#[derive(Component, Clone, Default)]
+pub struct Interpolated {
+    start: Option<(Vec3, Quat)>,
+    end: Option<(Vec3, Quat)>,
+    rendered: Option<(Vec3, Quat)>,
+}
+
+/// Steps further than this are wraps or teleports and are not smoothed
+const MAX_INTERPOLATION_DISTANCE: f32 = 200.0;
+
+fn interpolation_restore(mut query: Query<(&mut Transform, &mut Interpolated)>) {
+    for (mut transform, mut interpolated) in query.iter_mut() {
+        let current = (transform.translation, transform.rotation);
+        if interpolated.rendered != Some(current) {
+            // moved outside of the simulation or just spawned, take the new place as is
+            interpolated.start = None;
+            interpolated.end = Some(current);
+        } else if let Some((translation, rotation)) = interpolated.end {
+            transform.translation = translation;
+            transform.rotation = rotation;
+        }
+    }
+}
+
+fn interpolation_start(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.start = Some((transform.translation, transform.rotation));
+    }
+}
+
+fn interpolation_apply(
+    time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &mut Interpolated)>,
+) {
+    let fraction = time.overstep_fraction();
+    for (mut transform, mut interpolated) in query.iter_mut() {
+        let end = (transform.translation, transform.rotation);
+        let start = interpolated
+            .start
+            .filter(|start| start.0.distance(end.0) < MAX_INTERPOLATION_DISTANCE)
+            .unwrap_or(end);
+        transform.translation = start.0.lerp(end.0, fraction);
+        transform.rotation = start.1.slerp(end.1, fraction);
+        interpolated.end = Some(end);
+        interpolated.rendered = Some((transform.translation, transform.rotation));
+    }
+}
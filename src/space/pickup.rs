@@ -1,9 +1,11 @@
-use bevy::{math::FloatPow, prelude::*};
+use std::f32::consts::TAU;
+
+use bevy::{ecs::error, math::FloatPow, prelude::*};
 use rand::Rng;
 
 use crate::{
     layers::SpaceLayer,
-    materials::{DitherMaterial, GassyDither, RockyDither, SOLID_WHITE},
+    materials::{DitherMaterial, GassyDither, MetalDither, RockyDither, SOLID_WHITE},
     mesh::MeshLyonExtensions,
     resources::{ResourceType, Resources},
     scheduling::Sets,
@@ -12,12 +14,13 @@ use crate::{
 
 use super::{
     asteroid::generate_asteroid_shape,
+    health::heal,
     physics::{Spin, Velocity},
     ship::Ship,
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(Update, pickup_update.in_set(Sets::Update));
+    app.add_systems(FixedUpdate, pickup_update.in_set(Sets::Update));
 }
 
 #[derive(Component, Clone)]
@@ -37,18 +40,24 @@ const MAGNET_FORCE: f32 = 100.0;
 const PICKUP_RADIUS: f32 = 20.0;
 fn pickup_update(
     mut commands: Commands,
-    ship_query: Query<&Transform, With<Ship>>,
+    ship_query: Query<(Entity, &Transform), With<Ship>>,
     pickup_query: Query<(Entity, &Pickup, &Transform)>,
     mut resources: ResMut<Resources>,
 ) {
-    for ship_transform in ship_query.iter() {
+    for (ship, ship_transform) in ship_query.iter() {
         for (pickup_entity, pickup, pickup_transform) in pickup_query.iter() {
             let distance = ship_transform
                 .translation
                 .truncate()
                 .distance(pickup_transform.translation.truncate());
             if distance < PICKUP_RADIUS {
-                resources.add(pickup.resource, pickup.amount);
+                if pickup.resource == ResourceType::Health {
+                    commands
+                        .entity(ship)
+                        .queue_handled(heal(pickup.amount), error::ignore);
+                } else {
+                    resources.add(pickup.resource, pickup.amount);
+                }
                 commands.entity(pickup_entity).despawn();
             } else if distance < MAGNET_RADIUS {
                 let magnitude = 2.0 - (distance / MAGNET_RADIUS);
@@ -63,73 +72,153 @@ fn pickup_update(
     }
 }
 
-pub fn mineral_pickup(
+/// A pickup worth `amount` of `resource`, or the usual amount for that resource.
+pub fn pickup(
+    resource: ResourceType,
+    amount: Option<f32>,
     pos: Vec2,
+    rng: &mut impl Rng,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<DitherMaterial>>,
 ) -> impl Bundle {
-    let vertices = generate_asteroid_shape(5..=7, 10.0..=20.0);
-    (
-        Name::new("Mineral Pickup"),
-        Pickup::new(ResourceType::Mineral, 2.5),
-        SpaceLayer,
-        Mesh2d(meshes.add(Mesh::stroke_polygon(&vertices, 2.0))),
-        MeshMaterial2d(SOLID_WHITE),
-        Transform::from_xyz(
-            pos.x + rand::thread_rng().gen_range(-10.0..=10.0),
-            pos.y + rand::thread_rng().gen_range(-10.0..=10.0),
-            0.0,
+    let (vertices, material, default_amount): (_, DitherMaterial, _) = match resource {
+        ResourceType::Mineral => (
+            generate_asteroid_shape(rng, 5..=7, 10.0..=20.0),
+            RockyDither {
+                fill: 0.5,
+                scale: 20.0,
+                offset: vec2(rng.gen(), rng.gen()),
+            }
+            .into(),
+            2.5,
         ),
-        ZOrder::PICKUP,
-        Velocity::random(25.0..40.0),
-        Spin::random(90.0),
-        children![(
-            Name::new("Mineral Pickup Inner"),
-            SpaceLayer,
-            Mesh2d(meshes.add(Mesh::fill_polygon(&vertices))),
-            MeshMaterial2d(materials.add(RockyDither {
+        ResourceType::Gas => (
+            regular_polygon(8, 9.0),
+            GassyDither {
+                fill: 0.3,
+                scale: 15.0,
+            }
+            .into(),
+            2.0,
+        ),
+        ResourceType::Time => (
+            vec![
+                vec2(5.0, 0.0),
+                vec2(0.0, 10.0),
+                vec2(-5.0, 0.0),
+                vec2(0.0, -10.0),
+            ],
+            GassyDither {
                 fill: 0.5,
                 scale: 20.0,
-            })),
-            Transform::from_xyz(0.0, 0.0, 1.0),
-        )],
-    )
-}
-
-pub fn time_pickup(
-    pos: Vec2,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<DitherMaterial>>,
-) -> impl Bundle {
-    let vertices = vec![
-        vec2(5.0, 0.0),
-        vec2(0.0, 10.0),
-        vec2(-5.0, 0.0),
-        vec2(0.0, -10.0),
-    ];
+            }
+            .into(),
+            5.0,
+        ),
+        ResourceType::Health => (
+            vec![
+                vec2(9.0, -3.0),
+                vec2(9.0, 3.0),
+                vec2(3.0, 3.0),
+                vec2(3.0, 9.0),
+                vec2(-3.0, 9.0),
+                vec2(-3.0, 3.0),
+                vec2(-9.0, 3.0),
+                vec2(-9.0, -3.0),
+                vec2(-3.0, -3.0),
+                vec2(-3.0, -9.0),
+                vec2(3.0, -9.0),
+                vec2(3.0, -3.0),
+            ],
+            MetalDither {
+                fill: 0.8,
+                scale: 10.0,
+            }
+            .into(),
+            10.0,
+        ),
+        ResourceType::Ammo => (
+            vec![
+                vec2(8.0, 0.0),
+                vec2(4.0, 4.0),
+                vec2(-8.0, 4.0),
+                vec2(-8.0, -4.0),
+                vec2(4.0, -4.0),
+            ],
+            MetalDither {
+                fill: 0.5,
+                scale: 10.0,
+            }
+            .into(),
+            5.0,
+        ),
+        ResourceType::Rockets => (
+            vec![vec2(10.0, 0.0), vec2(-10.0, 7.0), vec2(-10.0, -7.0)],
+            MetalDither {
+                fill: 0.3,
+                scale: 10.0,
+            }
+            .into(),
+            1.0,
+        ),
+        ResourceType::Energy => (
+            vec![
+                vec2(3.0, 10.0),
+                vec2(-5.0, -1.0),
+                vec2(0.0, -1.0),
+                vec2(-3.0, -10.0),
+                vec2(5.0, 1.0),
+                vec2(0.0, 1.0),
+            ],
+            MetalDither {
+                fill: 0.6,
+                scale: 10.0,
+            }
+            .into(),
+            3.0,
+        ),
+        ResourceType::Fuel => (
+            vec![
+                vec2(-5.0, -8.0),
+                vec2(5.0, -8.0),
+                vec2(5.0, 5.0),
+                vec2(2.0, 8.0),
+                vec2(-5.0, 8.0),
+            ],
+            GassyDither {
+                fill: 0.7,
+                scale: 10.0,
+            }
+            .into(),
+            3.0,
+        ),
+    };
     (
-        Name::new("Time Pickup"),
-        Pickup::new(ResourceType::Time, 5.0),
+        Name::new(format!("{} Pickup", resource.to_string())),
+        Pickup::new(resource, amount.unwrap_or(default_amount)),
         SpaceLayer,
         Mesh2d(meshes.add(Mesh::stroke_polygon(&vertices, 2.0))),
         MeshMaterial2d(SOLID_WHITE),
         Transform::from_xyz(
-            pos.x + rand::thread_rng().gen_range(-10.0..=10.0),
-            pos.y + rand::thread_rng().gen_range(-10.0..=10.0),
+            pos.x + rng.gen_range(-10.0..=10.0),
+            pos.y + rng.gen_range(-10.0..=10.0),
             0.0,
         ),
         ZOrder::PICKUP,
-        Velocity::random(25.0..40.0),
-        Spin::random(45.0),
+        Velocity::random(rng, 25.0..40.0),
+        Spin::random(rng, 60.0),
         children![(
-            Name::new("Time Pickup Inner"),
+            Name::new(format!("{} Pickup Inner", resource.to_string())),
             SpaceLayer,
             Mesh2d(meshes.add(Mesh::fill_polygon(&vertices))),
-            MeshMaterial2d(materials.add(GassyDither {
-                fill: 0.5,
-                scale: 20.0,
-            })),
+            MeshMaterial2d(materials.add(material)),
             Transform::from_xyz(0.0, 0.0, 1.0),
         )],
     )
 }
+
+fn regular_polygon(corners: usize, radius: f32) -> Vec<Vec2> {
+    (0..corners)
+        .map(|i| Vec2::from_angle(TAU * i as f32 / corners as f32) * radius)
+        .collect()
+}
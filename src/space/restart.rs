@@ -8,7 +8,8 @@ use super::{
     gas::GasCloud,
     particles::Particle,
     pickup::Pickup,
-    ship::{ship_spawn, Ship, ShipBullet, ShipRocket},
+    ship::{ship_spawn, Ship},
+    weapon::{Mine, ShipBullet, ShipRocket},
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -26,6 +27,7 @@ fn space_restart(
     enemy_bullets: Query<Entity, With<EnemyBullet>>,
     ship_bullets: Query<Entity, With<ShipBullet>>,
     rockets: Query<Entity, With<ShipRocket>>,
+    mines: Query<Entity, With<Mine>>,
     particles: Query<Entity, With<Particle>>,
 ) {
     for entity in asteroids.iter() {
@@ -52,6 +54,9 @@ fn space_restart(
     for entity in rockets.iter() {
         commands.entity(entity).despawn();
     }
+    for entity in mines.iter() {
+        commands.entity(entity).despawn();
+    }
     for entity in particles.iter() {
         commands.entity(entity).despawn();
     }
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+
+use crate::scheduling::Sets;
+
+use super::{
+    collision::{Collider, Collisions, Layer},
+    physics::{Spin, Velocity},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, rigid_body_collide.in_set(Sets::Update));
+}
+
+/// Makes an entity bounce off other rigid bodies. Heavier bodies get pushed around less.
+/// Bodies with a [`Spin`] also start turning when hit off-centre, others keep their rotation.
+#[derive(Component, Clone, Debug)]
+#[require(Velocity(Vec2::ZERO), Restitution(0.5))]
+pub struct Mass(pub f32);
+
+/// How much of the speed toward each other is kept after a bounce, from 0 to 1
+#[derive(Component, Clone, Debug)]
+pub struct Restitution(pub f32);
+
+fn rigid_body_collide(
+    mut collisions: Collisions,
+    mut bodies: Query<(
+        &mut Transform,
+        &mut Velocity,
+        &Mass,
+        &Restitution,
+        &Collider,
+        Option<&mut Spin>,
+    )>,
+) {
+    for event in collisions.read(Layer::all(), Layer::all()) {
+        // every collision is reported both ways round, only resolve it once
+        if event.entity_a > event.entity_b {
+            continue;
+        }
+        let Ok([a, b]) = bodies.get_many_mut([event.entity_a, event.entity_b]) else {
+            continue;
+        };
+        let (mut transform_a, mut velocity_a, mass_a, restitution_a, collider_a, mut spin_a) = a;
+        let (mut transform_b, mut velocity_b, mass_b, restitution_b, collider_b, mut spin_b) = b;
+        let contact = &event.contact;
+        let normal = contact.normal;
+        let inverse_mass = (1.0 / mass_a.0, 1.0 / mass_b.0);
+        // solid discs are close enough for the moment of inertia
+        let inverse_inertia = |mass: &Mass, collider: &Collider, spin: &Option<Mut<Spin>>| {
+            if spin.is_some() {
+                2.0 / (mass.0 * collider.radius().powi(2))
+            } else {
+                0.0
+            }
+        };
+        let inverse_inertia = (
+            inverse_inertia(mass_a, collider_a, &spin_a),
+            inverse_inertia(mass_b, collider_b, &spin_b),
+        );
+        let total_inverse_mass = inverse_mass.0 + inverse_mass.1;
+
+        // push them apart so they stop overlapping
+        let depth = (-contact.dist).max(0.0);
+        let separation = normal * depth / total_inverse_mass;
+        transform_a.translation -= (separation * inverse_mass.0).extend(0.0);
+        transform_b.translation += (separation * inverse_mass.1).extend(0.0);
+
+        let point = (contact.point_a + contact.point_b) * 0.5;
+        let arm_a = point - transform_a.translation.truncate();
+        let arm_b = point - transform_b.translation.truncate();
+        let angular = |spin: &Option<Mut<Spin>>| spin.as_ref().map_or(0.0, |s| s.0.to_radians());
+        let point_velocity =
+            |velocity: Vec2, angular: f32, arm: Vec2| velocity + arm.perp() * angular;
+        let relative = point_velocity(velocity_b.0, angular(&spin_b), arm_b)
+            - point_velocity(velocity_a.0, angular(&spin_a), arm_a);
+        let approach = relative.dot(normal);
+        if approach >= 0.0 {
+            continue;
+        }
+
+        let restitution = restitution_a.0.min(restitution_b.0);
+        let impulse = -(1.0 + restitution) * approach
+            / (total_inverse_mass
+                + arm_a.perp_dot(normal).powi(2) * inverse_inertia.0
+                + arm_b.perp_dot(normal).powi(2) * inverse_inertia.1);
+        velocity_a.0 -= normal * impulse * inverse_mass.0;
+        velocity_b.0 += normal * impulse * inverse_mass.1;
+        if let Some(spin) = spin_a.as_mut() {
+            spin.0 -= (arm_a.perp_dot(normal) * impulse * inverse_inertia.0).to_degrees();
+        }
+        if let Some(spin) = spin_b.as_mut() {
+            spin.0 += (arm_b.perp_dot(normal) * impulse * inverse_inertia.1).to_degrees();
+        }
+    }
+}
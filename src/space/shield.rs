@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+
+use crate::{
+    layers::SpaceLayer,
+    materials::{DitherMaterial, MetalDither},
+    resources::{ResourceType, Resources},
+    scheduling::Sets,
+    sounds::Sounds,
+};
+
+use super::particles::EmitParticles;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, shield_regen.in_set(Sets::Update))
+        .add_systems(
+            Update,
+            (shield_update_bubble, shield_break_ring_tick).in_set(Sets::PostUpdate),
+        )
+        .add_observer(shield_break);
+}
+
+const BREAK_RING_LIFETIME: f32 = 0.4;
+
+/// Absorbs damage before health and recharges over time. See [`damage`](super::health::damage).
+#[derive(Component, Clone, Debug)]
+pub struct Shield {
+    pub strength: f32,
+    pub max: f32,
+    /// Strength regained per second
+    pub regen: f32,
+    /// Seconds after a hit before it starts recharging
+    pub recharge_delay: f32,
+    /// Energy used up for every point of strength regained
+    pub energy_cost: f32,
+    /// Seconds left before it recharges again
+    delay: f32,
+}
+
+impl Shield {
+    pub fn new(max: f32, regen: f32) -> Self {
+        Self {
+            strength: max,
+            max,
+            regen,
+            recharge_delay: 0.0,
+            energy_cost: 0.0,
+            delay: 0.0,
+        }
+    }
+
+    pub fn with_recharge_delay(self, recharge_delay: f32) -> Self {
+        Self {
+            recharge_delay,
+            ..self
+        }
+    }
+
+    /// Makes it recharge out of the ship's energy
+    pub fn powered(self, energy_cost: f32) -> Self {
+        Self {
+            energy_cost,
+            ..self
+        }
+    }
+
+    /// Takes as much of `damage` as the shield can, returning the rest.
+    pub fn absorb(&mut self, damage: f32) -> f32 {
+        let absorbed = damage.min(self.strength);
+        self.strength -= absorbed;
+        self.delay = self.recharge_delay;
+        damage - absorbed
+    }
+}
+
+/// Triggered on an entity when its shield is knocked out.
+#[derive(Event)]
+pub struct ShieldBreak;
+
+/// Ring drawn around anything with a [`Shield`] while it's up, fading as the shield weakens.
+#[derive(Component, Clone)]
+pub struct ShieldBubble;
+
+pub fn shield_bubble(
+    radius: f32,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<DitherMaterial>,
+) -> impl Bundle {
+    (
+        Name::new("Shield"),
+        SpaceLayer,
+        ShieldBubble,
+        Mesh2d(meshes.add(Annulus::new(radius, radius + 3.0))),
+        MeshMaterial2d(materials.add(MetalDither {
+            fill: 1.0,
+            scale: 10.0,
+        })),
+        Transform::from_xyz(0.0, 0.0, 0.2),
+    )
+}
+
+fn shield_regen(
+    mut shields: Query<&mut Shield>,
+    mut resources: ResMut<Resources>,
+    time: Res<Time>,
+) {
+    for mut shield in shields.iter_mut() {
+        if shield.delay > 0.0 {
+            shield.delay -= time.delta_secs();
+            continue;
+        }
+        if shield.strength >= shield.max {
+            continue;
+        }
+        let mut gain = (shield.regen * time.delta_secs()).min(shield.max - shield.strength);
+        if shield.energy_cost > 0.0 {
+            gain = gain.min(resources.get(ResourceType::Energy) / shield.energy_cost);
+            resources.add(ResourceType::Energy, -gain * shield.energy_cost);
+        }
+        if gain > 0.0 {
+            shield.strength += gain;
+        }
+    }
+}
+
+fn shield_update_bubble(
+    mut commands: Commands,
+    shields: Query<(&Shield, &Children), Changed<Shield>>,
+    bubbles: Query<&MeshMaterial2d<DitherMaterial>, With<ShieldBubble>>,
+    mut materials: ResMut<Assets<DitherMaterial>>,
+) {
+    for (shield, children) in shields.iter() {
+        for child in children.iter() {
+            let Ok(bubble) = bubbles.get(child) else {
+                continue;
+            };
+            if let Some(material) = materials.get_mut(&bubble.0) {
+                material.settings.fill = shield.strength / shield.max;
+            }
+            commands.entity(child).insert(if shield.strength > 0.0 {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            });
+        }
+    }
+}
+
+/// Flash of a shield bursting outwards when it breaks
+#[derive(Component, Clone)]
+pub struct ShieldBreakRing {
+    lifetime: f32,
+}
+
+fn shield_break(
+    trigger: Trigger<ShieldBreak>,
+    mut commands: Commands,
+    shielded: Query<(&Transform, &Children)>,
+    bubbles: Query<&Mesh2d, With<ShieldBubble>>,
+    mut materials: ResMut<Assets<DitherMaterial>>,
+    sounds: Res<Sounds>,
+    mut particles: EventWriter<EmitParticles>,
+) {
+    let Ok((transform, children)) = shielded.get(trigger.target()) else {
+        return;
+    };
+    particles.write(EmitParticles {
+        position: transform.translation.truncate(),
+        count: 15,
+    });
+    commands.spawn((
+        Name::new("Shield Break Sound"),
+        AudioPlayer::new(sounds.asteroid_break.clone()),
+        PlaybackSettings::DESPAWN,
+    ));
+    // reuse the bubble's mesh, the ring grows out from where the shield was
+    let Some(mesh) = children.iter().find_map(|child| bubbles.get(child).ok()) else {
+        return;
+    };
+    commands.entity(trigger.target()).with_child((
+        Name::new("Shield Break Ring"),
+        SpaceLayer,
+        ShieldBreakRing {
+            lifetime: BREAK_RING_LIFETIME,
+        },
+        mesh.clone(),
+        MeshMaterial2d(materials.add(MetalDither {
+            fill: 1.0,
+            scale: 10.0,
+        })),
+        Transform::from_xyz(0.0, 0.0, 0.2),
+    ));
+}
+
+fn shield_break_ring_tick(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut rings: Query<(
+        Entity,
+        &mut ShieldBreakRing,
+        &mut Transform,
+        &MeshMaterial2d<DitherMaterial>,
+    )>,
+    mut materials: ResMut<Assets<DitherMaterial>>,
+) {
+    for (entity, mut ring, mut transform, material) in rings.iter_mut() {
+        ring.lifetime -= time.delta_secs();
+        if ring.lifetime <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let left = ring.lifetime / BREAK_RING_LIFETIME;
+        transform.scale = Vec3::splat(2.0 - left);
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.settings.fill = left;
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::resources::ResourceType;
+
+/// What a weapon fires, which decides its starting stats and how its shots behave.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeaponKind {
+    /// Quick single bullets
+    Gun,
+    /// A fan of weak pellets
+    SpreadShot,
+    /// Slow, hard hitting slugs that go through everything in their way
+    Railgun,
+    /// Rockets that home in on enemies
+    RocketLauncher,
+    /// Leaves mines behind that go off when something comes close
+    MineLayer,
+    /// Beam that cuts into the closest asteroid, or whatever it's locked on to. Heats up
+    /// while firing.
+    MiningLaser,
+}
+
+impl WeaponKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            WeaponKind::Gun => "Gun",
+            WeaponKind::SpreadShot => "Spread Shot",
+            WeaponKind::Railgun => "Railgun",
+            WeaponKind::RocketLauncher => "Rocket Launcher",
+            WeaponKind::MineLayer => "Mine Layer",
+            WeaponKind::MiningLaser => "Mining Laser",
+        }
+    }
+
+    /// Damage per hit, or per second for beams
+    pub fn damage(self) -> f32 {
+        match self {
+            WeaponKind::Gun => 15.0,
+            WeaponKind::SpreadShot => 8.0,
+            WeaponKind::Railgun => 60.0,
+            WeaponKind::RocketLauncher => 50.0,
+            WeaponKind::MineLayer => 80.0,
+            WeaponKind::MiningLaser => 25.0,
+        }
+    }
+
+    /// Shots per second
+    pub fn fire_rate(self) -> f32 {
+        match self {
+            WeaponKind::Gun => 1.0 / 0.15,
+            WeaponKind::SpreadShot => 2.0,
+            WeaponKind::Railgun => 0.75,
+            WeaponKind::RocketLauncher => 0.2,
+            WeaponKind::MineLayer => 0.5,
+            WeaponKind::MiningLaser => 0.0,
+        }
+    }
+
+    /// Largest angle in radians a shot strays from where the ship points
+    pub fn spread(self) -> f32 {
+        match self {
+            WeaponKind::Gun => 0.05,
+            WeaponKind::SpreadShot => 0.35,
+            _ => 0.0,
+        }
+    }
+
+    /// Resource used up by every shot, if any
+    pub fn ammo(self) -> Option<ResourceType> {
+        match self {
+            WeaponKind::Gun | WeaponKind::SpreadShot | WeaponKind::Railgun => {
+                Some(ResourceType::Ammo)
+            }
+            WeaponKind::RocketLauncher | WeaponKind::MineLayer => Some(ResourceType::Rockets),
+            WeaponKind::MiningLaser => None,
+        }
+    }
+
+    /// How far shots fly before fizzling out. For mines it's how close something has to get
+    /// to set them off.
+    pub fn range(self) -> f32 {
+        match self {
+            WeaponKind::Gun => 1500.0,
+            WeaponKind::SpreadShot => 500.0,
+            WeaponKind::Railgun => 2500.0,
+            WeaponKind::RocketLauncher => 2000.0,
+            WeaponKind::MineLayer => 60.0,
+            WeaponKind::MiningLaser => 300.0,
+        }
+    }
+
+    /// Shots fired at once, fanned out over the spread
+    pub fn shots(self) -> u32 {
+        match self {
+            WeaponKind::SpreadShot => 5,
+            _ => 1,
+        }
+    }
+
+    /// Speed of bullets in pixels per second
+    pub fn bullet_speed(self) -> f32 {
+        match self {
+            WeaponKind::SpreadShot => 1200.0,
+            WeaponKind::Railgun => 3000.0,
+            _ => 1500.0,
+        }
+    }
+}
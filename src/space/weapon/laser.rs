@@ -0,0 +1,199 @@
+use bevy::{ecs::error, prelude::*};
+use rand::Rng;
+
+use crate::{
+    resources::{ResourceType, Resources},
+    rng::{GameRng, RngStream},
+    scheduling::Sets,
+    space::{
+        asteroid::Asteroid,
+        collision::{Collider, Contact},
+        enemy::Enemy,
+        gas::GasCloud,
+        health::{damage, Damage},
+        particles::EmitParticles,
+        ship::{LaserSound, Ship},
+    },
+};
+
+use super::{Weapon, WeaponKind, WeaponSlot};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Update, laser_lock.in_set(Sets::Input))
+        .add_systems(FixedUpdate, laser_beam.in_set(Sets::Update));
+}
+
+/// Locks the laser on to the next closest target in range, then back to mining on its own
+const LOCK_KEY: KeyCode = KeyCode::KeyF;
+/// Heat gained per second of beaming at level 1, it overheats at 1
+const HEAT_PER_SECOND: f32 = 0.25;
+const COOL_PER_SECOND: f32 = 0.4;
+/// Gas pulled out of a cloud per second
+const SIPHON_RATE: f32 = 1.5;
+
+/// Heat and target of a mining laser [`WeaponSlot`]
+#[derive(Component, Clone, Debug, Default)]
+pub struct Laser {
+    pub heat: f32,
+    /// Set when it overheats, it won't fire again until it has cooled right down
+    pub overheated: bool,
+    /// Asteroid, enemy or gas cloud picked with [`LOCK_KEY`], otherwise it goes for the
+    /// closest asteroid
+    pub target: Option<Entity>,
+}
+
+impl Laser {
+    /// Higher levels cut harder and run cooler
+    fn heat_rate(weapon: &Weapon) -> f32 {
+        HEAT_PER_SECOND / (1.0 + 0.25 * (weapon.level - 1) as f32)
+    }
+}
+
+fn laser_lock(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    ship: Single<&Transform, With<Ship>>,
+    mut lasers: Query<(&mut Laser, &Weapon)>,
+    targets: Query<(Entity, &Transform), Or<(With<Asteroid>, With<Enemy>, With<GasCloud>)>>,
+) {
+    if !keyboard_input.just_pressed(LOCK_KEY) {
+        return;
+    }
+    let position = ship.translation.truncate();
+    for (mut laser, weapon) in lasers.iter_mut() {
+        let mut in_range = targets
+            .iter()
+            .map(|(entity, transform)| {
+                (transform.translation.truncate().distance(position), entity)
+            })
+            .filter(|(distance, _)| *distance < weapon.range)
+            .collect::<Vec<_>>();
+        in_range.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let next = match laser
+            .target
+            .and_then(|target| in_range.iter().position(|(_, entity)| *entity == target))
+        {
+            Some(index) => in_range.get(index + 1),
+            None => in_range.first(),
+        };
+        laser.target = next.map(|(_, entity)| *entity);
+        match laser.target {
+            Some(target) => info!("Laser locked on to {target}"),
+            None => info!("Laser back to mining"),
+        }
+    }
+}
+
+fn laser_beam(
+    mut commands: Commands,
+    ship: Single<(&Transform, &Collider), With<Ship>>,
+    mut lasers: Query<(&WeaponSlot, &Weapon, &mut Laser)>,
+    targets: Query<(Entity, &Transform, &Collider)>,
+    asteroids: Query<(), With<Asteroid>>,
+    mut clouds: Query<&mut GasCloud>,
+    laser_sound: Single<&AudioSink, With<LaserSound>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut resources: ResMut<Resources>,
+    mut gizmos: Gizmos,
+    mut particles: EventWriter<EmitParticles>,
+    mut rng: ResMut<GameRng>,
+) {
+    let (ship_transform, ship_collider) = *ship;
+    let position = ship_transform.translation.truncate();
+    let mut is_lasering = false;
+    for (slot, weapon, mut laser) in lasers.iter_mut() {
+        if weapon.kind != WeaponKind::MiningLaser {
+            continue;
+        }
+        if laser
+            .target
+            .is_some_and(|target| targets.get(target).is_err())
+        {
+            laser.target = None;
+        }
+        let target = laser.target.or_else(|| {
+            targets
+                .iter()
+                .filter(|(entity, ..)| asteroids.contains(*entity))
+                .map(|(entity, transform, _)| {
+                    (transform.translation.truncate().distance(position), entity)
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(_, entity)| entity)
+        });
+        let contact = target.and_then(|target| {
+            let (_, transform, collider) = targets.get(target).ok()?;
+            let distance = transform.translation.truncate().distance(position);
+            if distance >= weapon.range {
+                return None;
+            }
+            Contact::query(ship_transform, ship_collider, transform, collider, distance)
+                .map(|contact| (target, contact))
+        });
+        let firing = !laser.overheated
+            && slot.key.is_none_or(|key| keyboard_input.pressed(key))
+            && contact.is_some();
+
+        if firing {
+            laser.heat += Laser::heat_rate(weapon) * time.delta_secs();
+            if laser.heat >= 1.0 {
+                laser.heat = 1.0;
+                laser.overheated = true;
+                info!("Mining laser overheated");
+            }
+        } else {
+            laser.heat = (laser.heat - COOL_PER_SECOND * time.delta_secs()).max(0.0);
+            if laser.heat == 0.0 {
+                laser.overheated = false;
+            }
+        }
+        laser_draw_heat(&mut gizmos, position, &laser);
+        if let Some((_, transform, _)) = laser.target.and_then(|target| targets.get(target).ok()) {
+            gizmos.circle_2d(transform.translation.truncate(), 12.0, Color::WHITE);
+        }
+
+        let Some((target, contact)) = contact.filter(|_| firing) else {
+            continue;
+        };
+        if rng.stream(RngStream::Weapons).gen_bool(0.1) {
+            particles.write(EmitParticles {
+                position: contact.point_b,
+                count: 1,
+            });
+        }
+        gizmos.line_2d(position, contact.point_b, Color::WHITE);
+        if let Ok(mut cloud) = clouds.get_mut(target) {
+            // gas clouds can't be hurt, the beam siphons gas out of them instead
+            let siphoned = (SIPHON_RATE * time.delta_secs()).min(cloud.remaining);
+            cloud.remaining -= siphoned;
+            resources.add(ResourceType::Gas, siphoned);
+        } else {
+            commands.entity(target).queue_handled(
+                damage(Damage::beam(time.delta_secs() * weapon.damage)),
+                error::ignore,
+            );
+        }
+        laser_sound.play();
+        is_lasering = true;
+    }
+    if !is_lasering && !laser_sound.is_paused() {
+        laser_sound.pause();
+    }
+}
+
+/// Bar under the ship that fills up as the laser heats
+fn laser_draw_heat(gizmos: &mut Gizmos, position: Vec2, laser: &Laser) {
+    const WIDTH: f32 = 40.0;
+    if laser.heat <= 0.0 {
+        return;
+    }
+    let start = position + vec2(-WIDTH / 2.0, -45.0);
+    gizmos.line_2d(start, start + vec2(WIDTH * laser.heat, 0.0), Color::WHITE);
+    if laser.overheated {
+        gizmos.rect_2d(
+            start + vec2(WIDTH / 2.0, 0.0),
+            vec2(WIDTH + 4.0, 6.0),
+            Color::WHITE,
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+use rand::Rng;
+
+use crate::{
+    resources::{ResourceType, Resources, UpgradeWeapon},
+    rng::{GameRng, RngStream},
+    scheduling::Sets,
+    sounds::Sounds,
+};
+
+use super::{enemy::Enemy, ship::Ship};
+
+mod kind;
+mod laser;
+mod projectile;
+
+pub use kind::WeaponKind;
+pub use laser::Laser;
+pub use projectile::{Mine, ShipBullet, ShipRocket};
+
+use projectile::{mine, ship_bullet, ship_rocket};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((laser::plugin, projectile::plugin))
+        .init_resource::<Arsenal>()
+        .add_systems(FixedUpdate, weapon_fire.in_set(Sets::Input))
+        .add_systems(Update, weapon_swap.in_set(Sets::Input))
+        .add_observer(weapon_upgrade);
+}
+
+/// Swaps the weapon in the [`PrimarySlot`] for the next one in the [`Arsenal`]
+const SWAP_KEY: KeyCode = KeyCode::Tab;
+
+const MAX_LEVEL: u32 = 5;
+
+/// Mount on the ship holding one [`Weapon`]. Fires while its key is held, or on its own at
+/// enemies in range when it has none.
+#[derive(Component, Clone, Debug)]
+pub struct WeaponSlot {
+    pub key: Option<KeyCode>,
+}
+
+/// The slot weapons from the [`Arsenal`] get swapped into
+#[derive(Component, Clone, Debug)]
+pub struct PrimarySlot;
+
+#[derive(Component, Clone, Debug)]
+pub struct Weapon {
+    pub kind: WeaponKind,
+    pub level: u32,
+    /// Per hit, or per second for beams
+    pub damage: f32,
+    /// Shots per second
+    pub fire_rate: f32,
+    /// Largest angle in radians a shot strays from where the ship points
+    pub spread: f32,
+    /// Resource used up by every shot, if any
+    pub ammo: Option<ResourceType>,
+    pub range: f32,
+    /// Seconds until it can fire again
+    cooldown: f32,
+}
+
+impl Weapon {
+    pub fn new(kind: WeaponKind) -> Self {
+        Self {
+            kind,
+            level: 1,
+            damage: kind.damage(),
+            fire_rate: kind.fire_rate(),
+            spread: kind.spread(),
+            ammo: kind.ammo(),
+            range: kind.range(),
+            cooldown: 0.0,
+        }
+    }
+
+    pub fn can_upgrade(&self) -> bool {
+        self.level < MAX_LEVEL
+    }
+
+    /// Makes it hit harder and fire faster
+    pub fn upgrade(&mut self) {
+        self.level += 1;
+        self.damage *= 1.25;
+        self.fire_rate *= 1.1;
+    }
+
+    /// Angles of every shot in one volley, relative to where the ship points
+    fn shot_angles(&self, rng: &mut impl Rng) -> Vec<f32> {
+        let shots = self.kind.shots();
+        if shots == 1 {
+            return vec![rng.gen_range(-self.spread..=self.spread)];
+        }
+        (0..shots)
+            .map(|i| -self.spread + 2.0 * self.spread * i as f32 / (shots - 1) as f32)
+            .collect()
+    }
+}
+
+/// Weapons the ship carries without having them equipped
+#[derive(Resource, Clone, Debug)]
+pub struct Arsenal(pub Vec<Weapon>);
+
+impl Default for Arsenal {
+    fn default() -> Self {
+        Self(vec![
+            Weapon::new(WeaponKind::SpreadShot),
+            Weapon::new(WeaponKind::Railgun),
+            Weapon::new(WeaponKind::MineLayer),
+        ])
+    }
+}
+
+pub fn weapon_slot(key: Option<KeyCode>, kind: WeaponKind) -> impl Bundle {
+    (
+        Name::new("Weapon Slot"),
+        WeaponSlot { key },
+        Weapon::new(kind),
+    )
+}
+
+fn weapon_swap(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut arsenal: ResMut<Arsenal>,
+    mut primary: Single<&mut Weapon, With<PrimarySlot>>,
+    sounds: Res<Sounds>,
+) {
+    if !keyboard_input.just_pressed(SWAP_KEY) || arsenal.0.is_empty() {
+        return;
+    }
+    let next = arsenal.0.remove(0);
+    let previous = std::mem::replace(&mut **primary, next);
+    arsenal.0.push(previous);
+    info!("Equipped {} level {}", primary.kind.name(), primary.level);
+    commands.spawn((
+        Name::new("Weapon Swap Sound"),
+        AudioPlayer::new(sounds.switch.clone()),
+        PlaybackSettings::DESPAWN,
+    ));
+}
+
+fn weapon_fire(
+    mut commands: Commands,
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    ship: Single<&Transform, With<Ship>>,
+    mut slots: Query<(&WeaponSlot, &mut Weapon)>,
+    enemies: Query<&Transform, With<Enemy>>,
+    mut resources: ResMut<Resources>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    sounds: Res<Sounds>,
+    mut rng: ResMut<GameRng>,
+) {
+    let rng = rng.stream(RngStream::Weapons);
+    let position = ship.translation.truncate();
+    let facing = ship.rotation.to_euler(EulerRot::YXZ).2;
+    for (slot, mut weapon) in slots.iter_mut() {
+        if weapon.kind == WeaponKind::MiningLaser {
+            continue;
+        }
+        weapon.cooldown = (weapon.cooldown - time.delta_secs()).max(0.0);
+        let triggered = match slot.key {
+            Some(key) => keyboard_input.pressed(key),
+            None => enemies
+                .iter()
+                .any(|enemy| enemy.translation.truncate().distance(position) < weapon.range),
+        };
+        if weapon.cooldown > 0.0 || !triggered {
+            continue;
+        }
+        if let Some(ammo) = weapon.ammo {
+            if resources.get(ammo) < 1.0 {
+                continue;
+            }
+            resources.add(ammo, -1.0);
+        }
+        weapon.cooldown = 1.0 / weapon.fire_rate;
+
+        match weapon.kind {
+            WeaponKind::RocketLauncher => {
+                commands.spawn(ship_rocket(
+                    &weapon,
+                    ship.translation,
+                    rng,
+                    &sounds,
+                    &mut meshes,
+                ));
+                commands.spawn((
+                    Name::new("Missile Launch Noise"),
+                    AudioPlayer::new(sounds.missile_launch.clone()),
+                    PlaybackSettings::DESPAWN,
+                ));
+            }
+            WeaponKind::MineLayer => {
+                commands.spawn(mine(&weapon, ship.translation, &mut meshes));
+            }
+            _ => {
+                for angle in weapon.shot_angles(rng) {
+                    commands.spawn(ship_bullet(
+                        &weapon,
+                        ship.translation,
+                        facing + angle,
+                        &mut meshes,
+                    ));
+                }
+                commands.spawn((
+                    Name::new("Shooty Gun Noise"),
+                    AudioPlayer::new(sounds.gun.clone()),
+                    PlaybackSettings {
+                        mode: PlaybackMode::Despawn,
+                        volume: Volume::Linear(0.4),
+                        ..default()
+                    },
+                ));
+            }
+        }
+    }
+}
+
+/// Upgrades the weakest equipped weapon
+fn weapon_upgrade(_trigger: Trigger<UpgradeWeapon>, mut weapons: Query<&mut Weapon>) {
+    let weakest = weapons
+        .iter_mut()
+        .filter(|weapon| weapon.can_upgrade())
+        .min_by_key(|weapon| weapon.level);
+    if let Some(mut weapon) = weakest {
+        weapon.upgrade();
+        info!("Upgraded {} to level {}", weapon.kind.name(), weapon.level);
+    }
+}
@@ -0,0 +1,318 @@
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    ecs::error,
+    prelude::*,
+};
+use rand::Rng;
+
+use crate::{
+    layers::SpaceLayer,
+    materials::SOLID_WHITE,
+    scheduling::Sets,
+    sounds::Sounds,
+    space::{
+        collision::{Collider, CollisionLayers, Collisions, FastMover, Layer},
+        enemy::Enemy,
+        health::{damage, Damage, Health},
+        particles::EmitParticles,
+        physics::{DespawnOutOfBounds, Rotation, Velocity},
+        ship::Ship,
+    },
+    z_order::ZOrder,
+};
+
+use super::{Weapon, WeaponKind};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (
+            (rocket_update, ship_bullet_range).in_set(Sets::Update),
+            (ship_bullet_collide, rocket_collide, mine_collide).in_set(Sets::Destroy),
+        ),
+    )
+    .add_observer(rocket_explode);
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct ShipBullet {
+    pub damage: Damage,
+    /// Distance left before it fizzles out
+    pub range: f32,
+    /// Keeps going after hitting something
+    pub pierce: bool,
+    /// Already hit by this bullet, so a piercing bullet doesn't hit them twice
+    hit: Vec<Entity>,
+}
+
+pub fn ship_bullet(
+    weapon: &Weapon,
+    translation: Vec3,
+    angle: f32,
+    meshes: &mut ResMut<Assets<Mesh>>,
+) -> impl Bundle {
+    let pierce = weapon.kind == WeaponKind::Railgun;
+    let radius = match weapon.kind {
+        WeaponKind::SpreadShot => 3.0,
+        _ => 5.0,
+    };
+    let mesh = if pierce {
+        meshes.add(Rectangle::new(30.0, 3.0))
+    } else {
+        meshes.add(Circle::new(radius))
+    };
+    (
+        Name::new("Ship Bullet"),
+        SpaceLayer,
+        ShipBullet {
+            damage: Damage::kinetic(weapon.damage),
+            range: weapon.range,
+            pierce,
+            hit: vec![],
+        },
+        Mesh2d(mesh),
+        MeshMaterial2d(SOLID_WHITE),
+        Collider::from_circle(radius),
+        CollisionLayers::new(Layer::SHIP_PROJECTILE, Layer::ENEMY | Layer::ASTEROID),
+        FastMover,
+        Transform::from_translation(translation),
+        ZOrder::BULLET,
+        Velocity(Vec2::from_angle(angle) * weapon.kind.bullet_speed()),
+        Rotation(angle.to_degrees()),
+        DespawnOutOfBounds,
+    )
+}
+
+fn ship_bullet_range(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bullets: Query<(Entity, &mut ShipBullet, &Velocity)>,
+) {
+    for (entity, mut bullet, velocity) in bullets.iter_mut() {
+        bullet.range -= velocity.0.length() * time.delta_secs();
+        if bullet.range <= 0.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn ship_bullet_collide(
+    mut commands: Commands,
+    mut collisions: Collisions,
+    mut ship_bullets: Query<&mut ShipBullet>,
+    targets: Query<(), With<Health>>,
+    mut particle_writer: EventWriter<EmitParticles>,
+) {
+    for event in collisions.read(Layer::SHIP_PROJECTILE, Layer::ENEMY | Layer::ASTEROID) {
+        let Ok(mut bullet) = ship_bullets.get_mut(event.entity_a) else {
+            continue;
+        };
+        if bullet.hit.contains(&event.entity_b) || !targets.contains(event.entity_b) {
+            continue;
+        }
+        commands
+            .entity(event.entity_b)
+            .queue_handled(damage(bullet.damage), error::ignore);
+        particle_writer.write(EmitParticles {
+            position: event.contact.point_b,
+            count: 3,
+        });
+        if bullet.pierce {
+            bullet.hit.push(event.entity_b);
+        } else {
+            commands.entity(event.entity_a).despawn();
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ShipRocket {
+    pub damage: Damage,
+}
+
+#[derive(Component, Clone)]
+pub struct MissileFlightSound;
+
+#[derive(Event)]
+pub struct RocketExplode;
+
+pub fn ship_rocket(
+    weapon: &Weapon,
+    translation: Vec3,
+    rng: &mut impl Rng,
+    sounds: &Sounds,
+    meshes: &mut ResMut<Assets<Mesh>>,
+) -> impl Bundle {
+    let mesh = meshes.add(Triangle2d::new(
+        vec2(5.0, 0.0),
+        vec2(-10.0, -5.0),
+        vec2(-10.0, 5.0),
+    ));
+    (
+        Name::new("Ship Rocket"),
+        SpaceLayer,
+        ShipRocket {
+            damage: Damage::explosive(weapon.damage),
+        },
+        Mesh2d(mesh),
+        MeshMaterial2d(SOLID_WHITE),
+        Collider::from_circle(5.0),
+        CollisionLayers::new(Layer::SHIP_PROJECTILE, Layer::ENEMY),
+        FastMover,
+        Transform::from_translation(translation),
+        ZOrder::BULLET,
+        Velocity::random(rng, 500.0..1000.0),
+        Rotation(0.0),
+        children![(
+            Name::new("Missile Flying Sound"),
+            MissileFlightSound,
+            AudioPlayer::new(sounds.missile_flight.clone()),
+            PlaybackSettings {
+                mode: PlaybackMode::Loop,
+                paused: true,
+                volume: Volume::Linear(0.3),
+                ..default()
+            },
+        )],
+    )
+}
+
+fn rocket_update(
+    mut commands: Commands,
+    mut rockets: Query<(Entity, &Transform, &mut Velocity, &mut Rotation), With<ShipRocket>>,
+    enemies: Query<&Transform, With<Enemy>>,
+    missile_flight_sounds: Query<&AudioSink, With<MissileFlightSound>>,
+    time: Res<Time>,
+    mut particles: EventWriter<EmitParticles>,
+) {
+    for (entity, transform, mut velocity, mut rotation) in rockets.iter_mut() {
+        if let Some(enemy_transform) = enemies.iter().next() {
+            let pos = transform.translation.truncate();
+            let target = enemy_transform.translation.truncate();
+            let angle = (target - pos).to_angle();
+            rotation.0 = angle.to_degrees();
+            velocity.0 = velocity
+                .0
+                .lerp((target - pos).normalize() * 1000.0, time.delta_secs() * 2.0);
+            particles.write(EmitParticles {
+                position: pos,
+                count: 1,
+            });
+        } else {
+            commands.trigger_targets(RocketExplode, entity);
+        }
+    }
+    for sound in missile_flight_sounds.iter() {
+        sound.play();
+    }
+}
+
+fn rocket_collide(
+    mut commands: Commands,
+    mut collisions: Collisions,
+    rockets: Query<&ShipRocket>,
+    enemies: Query<&Velocity, With<Enemy>>,
+) {
+    for event in collisions.read(Layer::SHIP_PROJECTILE, Layer::ENEMY) {
+        let Ok(rocket) = rockets.get(event.entity_a) else {
+            continue;
+        };
+        if let Ok(velocity) = enemies.get(event.entity_b) {
+            // Handle collision between rocket and enemy
+            let contact = &event.contact;
+            commands
+                .entity(event.entity_b)
+                .queue_handled(damage(rocket.damage), error::ignore)
+                .try_insert(Velocity(velocity.0 + contact.normal * 500.0));
+            commands.trigger_targets(RocketExplode, event.entity_a);
+            info!("Rocket collided with enemy at {:?}", contact.point_b);
+        }
+    }
+}
+
+fn rocket_explode(
+    trigger: Trigger<RocketExplode>,
+    mut commands: Commands,
+    rockets: Query<&Transform, With<ShipRocket>>,
+    sounds: Res<Sounds>,
+    mut particles: EventWriter<EmitParticles>,
+) {
+    if let Ok(transform) = rockets.get(trigger.target()) {
+        particles.write(EmitParticles {
+            position: transform.translation.truncate(),
+            count: 30,
+        });
+        commands.entity(trigger.target()).despawn();
+        commands.spawn((
+            Name::new("Boom Sound"),
+            AudioPlayer::new(sounds.enemy_die.clone()),
+            PlaybackSettings::DESPAWN,
+        ));
+    }
+}
+
+/// Sits still until an enemy or asteroid comes close, then blows up everything around it
+#[derive(Component, Clone, Debug)]
+pub struct Mine {
+    pub damage: Damage,
+    pub blast_radius: f32,
+}
+
+pub fn mine(weapon: &Weapon, translation: Vec3, meshes: &mut ResMut<Assets<Mesh>>) -> impl Bundle {
+    (
+        Name::new("Mine"),
+        SpaceLayer,
+        Mine {
+            damage: Damage::explosive(weapon.damage),
+            blast_radius: weapon.range * 2.0,
+        },
+        Mesh2d(meshes.add(RegularPolygon::new(8.0, 6))),
+        MeshMaterial2d(SOLID_WHITE),
+        // the collider is the proximity fuse, not the mine itself
+        Collider::from_circle(weapon.range),
+        CollisionLayers::new(Layer::SHIP_PROJECTILE, Layer::ENEMY | Layer::ASTEROID),
+        Transform::from_translation(translation),
+        ZOrder::BULLET,
+        Velocity(Vec2::ZERO),
+        Rotation(0.0),
+    )
+}
+
+fn mine_collide(
+    mut commands: Commands,
+    mut collisions: Collisions,
+    mines: Query<(&Mine, &Transform)>,
+    targets: Query<(Entity, &Transform), (With<Health>, Without<Ship>)>,
+    sounds: Res<Sounds>,
+    mut particles: EventWriter<EmitParticles>,
+) {
+    let mut exploded = vec![];
+    for event in collisions.read(Layer::SHIP_PROJECTILE, Layer::ENEMY | Layer::ASTEROID) {
+        let Ok((mine, transform)) = mines.get(event.entity_a) else {
+            continue;
+        };
+        if exploded.contains(&event.entity_a) {
+            continue;
+        }
+        exploded.push(event.entity_a);
+
+        let position = transform.translation.truncate();
+        for (target, target_transform) in targets.iter() {
+            if target_transform.translation.truncate().distance(position) < mine.blast_radius {
+                commands
+                    .entity(target)
+                    .queue_handled(damage(mine.damage), error::ignore);
+            }
+        }
+        particles.write(EmitParticles {
+            position,
+            count: 20,
+        });
+        commands.entity(event.entity_a).despawn();
+        commands.spawn((
+            Name::new("Mine Boom Sound"),
+            AudioPlayer::new(sounds.enemy_die.clone()),
+            PlaybackSettings::DESPAWN,
+        ));
+    }
+}